@@ -0,0 +1,295 @@
+use crate::{CountingRule, Dial, DialMovement, DialRotation, Direction};
+use std::f64::consts::TAU;
+use std::io::{self, BufRead, Write};
+use std::thread;
+use std::time::Duration;
+
+const CLEAR_SCREEN: &str = "\x1b[2J";
+const CURSOR_HOME: &str = "\x1b[H";
+const CLEAR_LINE: &str = "\x1b[K";
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationOptions {
+    /// How many clicks to draw per second. 0 draws as fast as the terminal allows.
+    pub clicks_per_second: u32,
+    /// Jump straight over the full turns of a rotation, drawing only the clicks left over.
+    pub skip_full_turns: bool,
+    /// Wait for Enter whenever a click lands on 0.
+    pub pause_on_zero: bool,
+    pub show_counts: bool,
+    /// Radius of the drawn dial in terminal rows. Must be at least 1.
+    pub radius: usize,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> AnimationOptions {
+        AnimationOptions { clicks_per_second: 60, skip_full_turns: false, pause_on_zero: false, show_counts: true, radius: 8 }
+    }
+}
+
+impl AnimationOptions {
+    pub const USAGE: &str = "[--speed <clicks per second>] [--skip-full-turns] [--pause-on-zero] [--no-counts] [--radius <rows>] [rotations file]";
+
+    /// Reads options from command line arguments, returning them with any arguments that are not options.
+    pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<(AnimationOptions, Vec<String>), String> {
+        let mut options = AnimationOptions::default();
+        let mut rest = Vec::new();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--speed" => {
+                    let speed = args.next().ok_or("--speed needs a number of clicks per second")?;
+                    options.clicks_per_second = speed.parse().map_err(|_| format!("Invalid speed {:?}", speed))?;
+                },
+                "--skip-full-turns" => options.skip_full_turns = true,
+                "--pause-on-zero" => options.pause_on_zero = true,
+                "--no-counts" => options.show_counts = false,
+                "--radius" => {
+                    let radius = args.next().ok_or("--radius needs a number of rows")?;
+                    options.radius = radius.parse().ok().filter(|&radius| radius >= 1).ok_or_else(|| format!("Invalid radius {:?}", radius))?;
+                },
+                _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+                _ => rest.push(arg),
+            }
+        }
+        Ok((options, rest))
+    }
+
+    fn click_delay(&self) -> Option<Duration> {
+        (self.clicks_per_second > 0).then(|| Duration::from_secs(1) / self.clicks_per_second)
+    }
+}
+
+/// Draws a dial as a ring of ticks running clockwise from 0 at the top, with `●` at `position`.
+/// Dials with more positions than ticks share a tick between neighbouring positions. A radius of
+/// 0 is drawn as 1, and a label too wide for the dial is cut off at its edge.
+pub fn render_circular_dial(size: i32, position: i32, radius: usize) -> Vec<String> {
+    let radius = radius.max(1);
+    let height = 2 * radius + 1;
+    // Terminal cells are about twice as tall as they are wide.
+    let width = 4 * radius + 1;
+    let mut grid = vec![vec![' '; width]; height];
+    let ticks = size.min(8 * radius as i32);
+
+    let mut mark = |tick: i32, symbol: char| {
+        let angle = TAU * tick as f64 / ticks as f64;
+        let x = (2 * radius) as f64 + (2 * radius) as f64 * angle.sin();
+        let y = radius as f64 - radius as f64 * angle.cos();
+        grid[y.round() as usize][x.round() as usize] = symbol;
+    };
+    for tick in 1..ticks {
+        mark(tick, '·');
+    }
+    mark(0, '0');
+    mark((position as i64 * ticks as i64 / size as i64) as i32, '●');
+
+    let label = position.to_string();
+    let start = (2 * radius + 1).saturating_sub(label.len().div_ceil(2));
+    for (offset, symbol) in label.chars().take(width - start).enumerate() {
+        grid[radius][start + offset] = symbol;
+    }
+
+    grid.into_iter().map(|row| row.into_iter().collect::<String>().trim_end().to_string()).collect()
+}
+
+/// Plays rotations on a terminal one click at a time, keeping both puzzle counts as it goes.
+pub struct Animator<W, R> {
+    output: W,
+    pause_input: R,
+    options: AnimationOptions,
+    rotations_done: usize,
+    zero_landings: u64,
+    zero_clicks: u64,
+}
+
+impl<W: Write, R: BufRead> Animator<W, R> {
+    /// `pause_input` is read a line at a time when pausing on zero.
+    pub fn new(output: W, pause_input: R, options: AnimationOptions) -> Animator<W, R> {
+        Animator { output, pause_input, options, rotations_done: 0, zero_landings: 0, zero_clicks: 0 }
+    }
+
+    pub fn count(&self, rule: CountingRule) -> u64 {
+        match rule {
+            CountingRule::LandingsOnZero => self.zero_landings,
+            CountingRule::ClicksThroughZero => self.zero_clicks,
+        }
+    }
+
+    pub fn start(&mut self, dial: &Dial) -> io::Result<()> {
+        write!(self.output, "{}{}", HIDE_CURSOR, CLEAR_SCREEN)?;
+        self.draw(dial.get_size(), dial.get_current_position(), "Start")
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        write!(self.output, "{}", SHOW_CURSOR)?;
+        self.output.flush()
+    }
+
+    /// Turns `dial` through `rotation`, drawing a frame for every click.
+    pub fn animate(&mut self, dial: &mut Dial, rotation: &DialRotation) -> io::Result<DialMovement> {
+        let size = dial.get_size();
+        let step = match rotation.get_direction() {
+            Direction::Left => -1,
+            Direction::Right => 1,
+        };
+        let caption = format!("Rotation {}: {}", self.rotations_done + 1, rotation);
+        let mut position = dial.get_current_position();
+        let mut clicks = rotation.get_value();
+
+        if self.options.skip_full_turns && clicks >= size {
+            let full_turns = (clicks / size) as u64;
+            clicks %= size;
+            // Every full turn passes 0 exactly once.
+            self.zero_clicks += full_turns;
+            self.draw(size, position, &format!("{}   skipped {} full turn{}", caption, full_turns, if full_turns == 1 { "" } else { "s" }))?;
+            self.wait(true)?;
+        }
+
+        for _ in 0..clicks {
+            position = (position + step).rem_euclid(size);
+            let hit_zero = position == 0;
+            self.zero_clicks += hit_zero as u64;
+            self.draw(size, position, &if hit_zero { format!("{}   click on 0!", caption) } else { caption.clone() })?;
+            self.wait(hit_zero)?;
+        }
+
+        let movement = dial.rotate(rotation);
+        self.zero_landings += movement.landed_on_zero() as u64;
+        self.rotations_done += 1;
+        self.draw(size, movement.end, &format!("{}   done", caption))?;
+        Ok(movement)
+    }
+
+    fn draw(&mut self, size: i32, position: i32, caption: &str) -> io::Result<()> {
+        write!(self.output, "{}", CURSOR_HOME)?;
+        for line in render_circular_dial(size, position, self.options.radius) {
+            writeln!(self.output, "{}{}", line, CLEAR_LINE)?;
+        }
+        writeln!(self.output, "\n{}{}", caption, CLEAR_LINE)?;
+        if self.options.show_counts {
+            writeln!(
+                self.output,
+                "Landings on 0 (part 1): {}   Clicks through 0 (part 2): {}{}",
+                self.zero_landings, self.zero_clicks, CLEAR_LINE
+            )?;
+        }
+        self.output.flush()
+    }
+
+    fn wait(&mut self, hit_zero: bool) -> io::Result<()> {
+        if hit_zero && self.options.pause_on_zero {
+            write!(self.output, "Press Enter to continue{}", CLEAR_LINE)?;
+            self.output.flush()?;
+            self.pause_input.read_line(&mut String::new())?;
+            write!(self.output, "\r{}", CLEAR_LINE)?;
+        } else if let Some(delay) = self.options.click_delay() {
+            thread::sleep(delay);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_dial_rotation, test_rotations};
+
+    fn fast(skip_full_turns: bool, pause_on_zero: bool) -> AnimationOptions {
+        AnimationOptions { clicks_per_second: 0, skip_full_turns, pause_on_zero, show_counts: true, radius: 8 }
+    }
+
+    #[test]
+    fn animating_test_input_counts_both_parts() {
+        for skip_full_turns in [false, true] {
+            let mut dial = Dial::new(50, 0).unwrap();
+            let mut animator = Animator::new(Vec::new(), io::empty(), fast(skip_full_turns, false));
+
+            animator.start(&dial).unwrap();
+            for rotation in test_rotations() {
+                animator.animate(&mut dial, &rotation).unwrap();
+            }
+            animator.finish().unwrap();
+
+            assert_eq!(dial.get_current_position(), 32);
+            assert_eq!(animator.count(CountingRule::LandingsOnZero), 3);
+            assert_eq!(animator.count(CountingRule::ClicksThroughZero), 6);
+        }
+    }
+
+    #[test]
+    fn skipping_full_turns_still_counts_their_zero_clicks() {
+        let mut dial = Dial::new(0, 0).unwrap();
+        let mut animator = Animator::new(Vec::new(), io::empty(), fast(true, false));
+
+        // Leaving 0 doesn't count it, so L5 then R1000 pass 0 ten times, and R5 lands back on it.
+        for rotation in ["L5", "R1000", "R5"] {
+            animator.animate(&mut dial, &get_dial_rotation(rotation)).unwrap();
+        }
+
+        assert_eq!(animator.count(CountingRule::ClicksThroughZero), 11);
+        assert_eq!(animator.count(CountingRule::LandingsOnZero), 1);
+    }
+
+    #[test]
+    fn pausing_waits_for_a_line_at_every_zero_click() {
+        let mut dial = Dial::new(50, 0).unwrap();
+        let mut input = "\n\n\nleft over\n".as_bytes();
+        let mut output = Vec::new();
+        {
+            let mut animator = Animator::new(&mut output, &mut input, fast(false, true));
+            animator.animate(&mut dial, &get_dial_rotation("R250")).unwrap();
+            assert_eq!(animator.count(CountingRule::ClicksThroughZero), 3);
+        }
+
+        assert_eq!(input, b"left over\n");
+        assert_eq!(String::from_utf8(output).unwrap().matches("Press Enter").count(), 3);
+    }
+
+    #[test]
+    fn frames_are_drawn_in_place() {
+        let mut output = Vec::new();
+        Animator::new(&mut output, io::empty(), fast(false, false))
+            .animate(&mut Dial::new(0, 0).unwrap(), &get_dial_rotation("L5"))
+            .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        // One frame per click and one for the finished rotation.
+        assert_eq!(output.matches(CURSOR_HOME).count(), 6);
+        assert!(output.contains("Rotation 1: L5   done"));
+        assert!(output.contains("Landings on 0 (part 1): 0   Clicks through 0 (part 2): 0"));
+    }
+
+    #[test]
+    fn circular_dial_marks_zero_and_position() {
+        let top = render_circular_dial(100, 0, 4);
+        assert_eq!(top.len(), 9);
+        assert_eq!(top[0].trim(), "·· ● ··");
+        assert_eq!(top[4].trim(), "·       0       ·");
+
+        let right = render_circular_dial(100, 25, 4);
+        assert_eq!(right[0].trim(), "·· 0 ··");
+        assert!(right[4].ends_with("25      ●"), "{:?}", right[4]);
+    }
+
+    #[test]
+    fn tiny_circular_dials_still_draw() {
+        assert_eq!(render_circular_dial(100, 0, 0), render_circular_dial(100, 0, 1));
+        assert_eq!(render_circular_dial(i32::MAX, i32::MAX - 1, 1)[1], "21474");
+    }
+
+    #[test]
+    fn options_parse_from_arguments() {
+        let args = ["--speed", "5", "input/test_input.txt", "--skip-full-turns", "--no-counts", "--radius", "3"].map(String::from);
+
+        let (options, rest) = AnimationOptions::parse_args(args).unwrap();
+
+        assert_eq!(options, AnimationOptions { clicks_per_second: 5, skip_full_turns: true, pause_on_zero: false, show_counts: false, radius: 3 });
+        assert_eq!(rest, vec!["input/test_input.txt"]);
+        assert!(AnimationOptions::parse_args(["--speed".to_string()]).is_err());
+        assert!(AnimationOptions::parse_args(["--fast".to_string()]).is_err());
+        assert!(AnimationOptions::parse_args(["--radius", "0"].map(String::from)).is_err());
+    }
+}
@@ -0,0 +1,266 @@
+use crate::{count_target_clicks, count_zero_clicks, CountingRule, Dial, DialRotation};
+use std::fmt;
+use std::str::FromStr;
+
+/// What happens to the next wheel when a wheel wraps round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CarryRule {
+    /// The wheel wraps without moving anything else.
+    Free,
+    /// Every time the wheel wraps from its top position to 0 the next wheel turns `clicks` right,
+    /// and every time it wraps from 0 to its top position the next wheel turns `clicks` left.
+    Geared { clicks: i32 },
+}
+
+impl CarryRule {
+    /// The usual odometer rule: one click of the next wheel per wrap.
+    pub const ODOMETER: CarryRule = CarryRule::Geared { clicks: 1 };
+}
+
+#[derive(Debug, Clone)]
+pub struct BankWheel {
+    dial: Dial,
+    carry: CarryRule,
+    zero_landings: u64,
+    zero_clicks: u64,
+}
+
+impl BankWheel {
+    pub fn new(dial: Dial, carry: CarryRule) -> BankWheel {
+        BankWheel { dial, carry, zero_landings: 0, zero_clicks: 0 }
+    }
+
+    pub fn dial(&self) -> &Dial {
+        &self.dial
+    }
+
+    pub fn carry(&self) -> CarryRule {
+        self.carry
+    }
+
+    pub fn zero_count(&self, rule: CountingRule) -> u64 {
+        match rule {
+            CountingRule::LandingsOnZero => self.zero_landings,
+            CountingRule::ClicksThroughZero => self.zero_clicks,
+        }
+    }
+
+    /// Turns the wheel by a signed number of clicks and returns the signed number of wraps.
+    fn turn(&mut self, amount: i64) -> i64 {
+        let size = self.dial.get_size() as i64;
+        let start = self.dial.get_current_position() as i64;
+
+        let zero_clicks = count_zero_clicks(start, amount, size);
+        let wraps = if amount >= 0 {
+            zero_clicks as i64
+        } else {
+            // Turning left, every arrival at the top position comes from 0.
+            -(count_target_clicks(start, amount, size - 1, size) as i64)
+        };
+
+        let end = (start + amount).rem_euclid(size) as i32;
+        self.dial.set_dial_to_new_position(end);
+        self.zero_clicks += zero_clicks;
+        self.zero_landings += (end == 0) as u64;
+        wraps
+    }
+}
+
+/// A rotation aimed at one wheel of a `DialBank`. Written `2:L68`, or just `L68` for wheel 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BankRotation {
+    pub wheel: usize,
+    pub rotation: DialRotation,
+}
+
+impl FromStr for BankRotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (wheel, rotation) = match s.split_once(':') {
+            Some((wheel, rotation)) => {
+                let wheel = wheel.trim().parse::<usize>().map_err(|_| format!("Invalid wheel index in bank rotation {:?}", s))?;
+                (wheel, rotation)
+            },
+            None => (0, s),
+        };
+        let rotation = rotation.parse::<DialRotation>().map_err(|error| error.to_string())?;
+        Ok(BankRotation { wheel, rotation })
+    }
+}
+
+impl fmt::Display for BankRotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.wheel, self.rotation)
+    }
+}
+
+/// A row of wheels, least significant first, where wrapping one wheel can turn the next one
+/// according to its `CarryRule`. Carries out of the last wheel are counted as overflows.
+#[derive(Debug, Clone)]
+pub struct DialBank {
+    wheels: Vec<BankWheel>,
+    all_zero_landings: u64,
+    overflows: i64,
+}
+
+impl DialBank {
+    pub fn new(wheels: Vec<BankWheel>) -> Result<DialBank, String> {
+        if wheels.is_empty() {
+            return Err("A dial bank needs at least one wheel".to_string());
+        }
+        Ok(DialBank { wheels, all_zero_landings: 0, overflows: 0 })
+    }
+
+    /// A single free wheel, which behaves exactly like `dial` under both counting rules.
+    pub fn single(dial: Dial) -> DialBank {
+        DialBank { wheels: vec![BankWheel::new(dial, CarryRule::Free)], all_zero_landings: 0, overflows: 0 }
+    }
+
+    pub fn odometer(size: i32, wheel_count: usize) -> Result<DialBank, String> {
+        let wheels = (0..wheel_count)
+            .map(|_| Ok(BankWheel::new(Dial::with_size(size, 0, 0)?, CarryRule::ODOMETER)))
+            .collect::<Result<Vec<_>, String>>()?;
+        DialBank::new(wheels)
+    }
+
+    pub fn wheels(&self) -> &[BankWheel] {
+        &self.wheels
+    }
+
+    pub fn positions(&self) -> Vec<i32> {
+        self.wheels.iter().map(|wheel| wheel.dial.get_current_position()).collect()
+    }
+
+    /// The net number of wraps carried out of the last wheel, negative for borrows.
+    pub fn overflows(&self) -> i64 {
+        self.overflows
+    }
+
+    pub fn zero_count(&self, rule: CountingRule) -> u64 {
+        self.wheels.iter().map(|wheel| wheel.zero_count(rule)).sum()
+    }
+
+    /// How many rotations left every wheel of the bank on 0.
+    pub fn all_zero_landings(&self) -> u64 {
+        self.all_zero_landings
+    }
+
+    pub fn rotate(&mut self, bank_rotation: &BankRotation) -> Result<(), String> {
+        if bank_rotation.wheel >= self.wheels.len() {
+            return Err(format!("Bank has no wheel {}", bank_rotation.wheel));
+        }
+
+        let mut amount = bank_rotation.rotation.get_signed_value();
+        for index in bank_rotation.wheel..self.wheels.len() {
+            if amount == 0 {
+                break;
+            }
+
+            let wraps = self.wheels[index].turn(amount);
+            amount = match self.wheels[index].carry {
+                CarryRule::Free => 0,
+                CarryRule::Geared { clicks } => wraps
+                    .checked_mul(clicks as i64)
+                    .ok_or_else(|| format!("Carry out of wheel {} overflowed", index))?,
+            };
+        }
+        self.overflows += amount;
+
+        if self.wheels.iter().all(|wheel| wheel.dial.dial_is_at_zero()) {
+            self.all_zero_landings += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_dial_rotation, TEST_INPUT};
+
+    fn bank_rotation(text: &str) -> BankRotation {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn single_wheel_bank_matches_both_parts_for_test_input() {
+        let mut bank = DialBank::single(Dial::new(50, 0).unwrap());
+
+        for text in TEST_INPUT {
+            bank.rotate(&bank_rotation(text)).unwrap();
+        }
+
+        assert_eq!(bank.zero_count(CountingRule::LandingsOnZero), 3);
+        assert_eq!(bank.zero_count(CountingRule::ClicksThroughZero), 6);
+        assert_eq!(bank.positions(), vec![32]);
+    }
+
+    #[test]
+    fn odometer_counts_like_a_decimal_number() {
+        let mut bank = DialBank::odometer(10, 3).unwrap();
+
+        bank.rotate(&bank_rotation("R987")).unwrap();
+        assert_eq!(bank.positions(), vec![7, 8, 9]);
+
+        bank.rotate(&bank_rotation("0:R15")).unwrap();
+        assert_eq!(bank.positions(), vec![2, 0, 0]);
+        assert_eq!(bank.overflows(), 1);
+
+        bank.rotate(&bank_rotation("0:L3")).unwrap();
+        assert_eq!(bank.positions(), vec![9, 9, 9]);
+        assert_eq!(bank.overflows(), 0);
+    }
+
+    #[test]
+    fn rotating_a_higher_wheel_leaves_lower_wheels_alone() {
+        let mut bank = DialBank::odometer(10, 3).unwrap();
+
+        bank.rotate(&bank_rotation("1:R25")).unwrap();
+
+        assert_eq!(bank.positions(), vec![0, 5, 2]);
+        assert_eq!(bank.wheels()[1].zero_count(CountingRule::ClicksThroughZero), 2);
+        assert_eq!(bank.wheels()[2].zero_count(CountingRule::ClicksThroughZero), 0);
+    }
+
+    #[test]
+    fn geared_carry_moves_the_next_wheel_several_clicks() {
+        let wheels = vec![
+            BankWheel::new(Dial::with_size(12, 0, 0).unwrap(), CarryRule::Geared { clicks: 5 }),
+            BankWheel::new(Dial::with_size(60, 0, 0).unwrap(), CarryRule::Free),
+        ];
+        let mut bank = DialBank::new(wheels).unwrap();
+
+        bank.rotate(&bank_rotation("L25")).unwrap();
+
+        assert_eq!(bank.positions(), vec![11, 45]);
+    }
+
+    #[test]
+    fn all_zero_landings_count_the_whole_bank_reading_zero() {
+        let mut bank = DialBank::odometer(10, 2).unwrap();
+
+        bank.rotate(&bank_rotation("R100")).unwrap();
+        bank.rotate(&bank_rotation("R5")).unwrap();
+        bank.rotate(&bank_rotation("L5")).unwrap();
+
+        assert_eq!(bank.all_zero_landings(), 2);
+        assert_eq!(bank.zero_count(CountingRule::LandingsOnZero), 3);
+    }
+
+    #[test]
+    fn bad_bank_rotations_are_errors() {
+        assert!("x:L5".parse::<BankRotation>().is_err());
+        assert!("1:Q5".parse::<BankRotation>().is_err());
+        assert!(DialBank::odometer(10, 2).unwrap().rotate(&bank_rotation("2:L5")).is_err());
+        assert!(DialBank::new(vec![]).is_err());
+    }
+
+    #[test]
+    fn bank_rotation_displays_with_its_wheel() {
+        let rotation = BankRotation { wheel: 3, rotation: get_dial_rotation("R7") };
+
+        assert_eq!(rotation.to_string(), "3:R7");
+        assert_eq!(rotation.to_string().parse::<BankRotation>(), Ok(rotation));
+    }
+}
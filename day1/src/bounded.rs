@@ -0,0 +1,306 @@
+use crate::{CountingPolicy, DialMovement, DialRotation, Direction};
+
+/// What a `BoundedDial` does when a rotation would take it past one of its end stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Stop at the limit and drop the remaining clicks.
+    Clamp,
+    /// Refuse the whole rotation and leave the dial where it was.
+    Error,
+    /// Bounce off the limit and carry on the other way.
+    Reflect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundedMovement {
+    pub start: i32,
+    pub rotation: DialRotation,
+    pub end: i32,
+    pub stop_hits: u64,
+}
+
+/// A dial with end stops at 0 and `size - 1` instead of wrapping round.
+#[derive(Debug, Clone)]
+pub struct BoundedDial {
+    size: i32,
+    current_position: i32,
+    overflow_policy: OverflowPolicy,
+    stop_hits: u64,
+}
+
+impl BoundedDial {
+    pub fn new(size: i32, current_position: i32, overflow_policy: OverflowPolicy) -> Result<BoundedDial, String> {
+        if size < 1 {
+            return Err("Invalid dial size".to_string());
+        }
+        if current_position < 0 || current_position >= size {
+            return Err("Invalid dial starting position".to_string());
+        }
+        Ok(BoundedDial { size, current_position, overflow_policy, stop_hits: 0 })
+    }
+
+    pub fn get_size(&self) -> i32 {
+        self.size
+    }
+
+    pub fn get_current_position(&self) -> i32 {
+        self.current_position
+    }
+
+    pub fn get_overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
+    }
+
+    /// How many times a rotation has run into an end stop (each bounce counts under `Reflect`).
+    pub fn get_stop_hits(&self) -> u64 {
+        self.stop_hits
+    }
+
+    pub fn turn(&mut self, rotation: &DialRotation) -> Result<BoundedMovement, String> {
+        self.turn_with(rotation, &mut NoCounting)
+    }
+
+    /// Turns the dial, handing each straight leg of the move to `policy` as a `DialMovement`.
+    /// Legs never cross an end stop, so the wrapping arithmetic in the counting rules sees exactly
+    /// the clicks the bounded dial makes. Policies that judge whole rotations are handed one
+    /// movement from `start` to `end` instead, so bouncing off 0 is not a landing.
+    pub fn turn_with<P: CountingPolicy>(&mut self, rotation: &DialRotation, policy: &mut P) -> Result<BoundedMovement, String> {
+        if !policy.judges_whole_rotations() {
+            return self.turn_legs(rotation, policy);
+        }
+
+        let movement = self.turn_legs(rotation, &mut NoCounting)?;
+        policy.observe(&DialMovement { size: self.size, start: movement.start, rotation: *rotation, end: movement.end });
+        Ok(movement)
+    }
+
+    /// Bouncing takes O(1) however far the dial turns: the round trips between the stops are all
+    /// the same, so they are handed to `policy.observe_repeated` in one go.
+    fn turn_legs<P: CountingPolicy>(&mut self, rotation: &DialRotation, policy: &mut P) -> Result<BoundedMovement, String> {
+        let start = self.current_position;
+        let top = self.size - 1;
+        let direction = rotation.get_direction();
+        let amount = rotation.get_value();
+        let (stop, room) = match direction {
+            Direction::Left => (0, start),
+            Direction::Right => (top, top - start),
+        };
+
+        let mut observe_leg = |from: i32, direction: Direction, clicks: i32| {
+            let movement = DialMovement::new(self.size, from, DialRotation::new(direction, clicks));
+            if clicks > 0 {
+                policy.observe(&movement);
+            }
+            movement.end
+        };
+
+        let (end, stop_hits) = if amount <= room {
+            (observe_leg(start, direction, amount), 0)
+        } else {
+            match self.overflow_policy {
+                OverflowPolicy::Error => {
+                    return Err(format!("Rotation {} from {} runs past the end stop of a {}-position dial", rotation, start, self.size));
+                },
+                OverflowPolicy::Clamp => (observe_leg(start, direction, room), 1),
+                // With a single position there is nowhere to bounce to, so it behaves like a clamp.
+                OverflowPolicy::Reflect if top == 0 => (start, 1),
+                OverflowPolicy::Reflect => {
+                    observe_leg(start, direction, room);
+
+                    // Every bounce is followed by a sweep of `top` clicks to the other stop,
+                    // except the last, which stops part way.
+                    let remaining = amount - room;
+                    let (sweeps, rest) = (remaining / top, remaining % top);
+                    let away = DialMovement::new(self.size, stop, DialRotation::new(direction.opposite(), top));
+                    let back = DialMovement::new(self.size, away.end, DialRotation::new(direction, top));
+                    policy.observe_repeated(&[away, back], (sweeps / 2) as u64);
+
+                    let (mut position, mut heading) = (stop, direction.opposite());
+                    if sweeps % 2 == 1 {
+                        policy.observe(&away);
+                        (position, heading) = (away.end, direction);
+                    }
+                    if rest > 0 {
+                        let leg = DialMovement::new(self.size, position, DialRotation::new(heading, rest));
+                        policy.observe(&leg);
+                        position = leg.end;
+                    }
+                    (position, sweeps as u64 + (rest > 0) as u64)
+                },
+            }
+        };
+
+        self.current_position = end;
+        self.stop_hits += stop_hits;
+        Ok(BoundedMovement { start, rotation: *rotation, end, stop_hits })
+    }
+}
+
+struct NoCounting;
+
+impl CountingPolicy for NoCounting {
+    fn observe(&mut self, _movement: &DialMovement) {}
+
+    fn observe_repeated(&mut self, _movements: &[DialMovement], _times: u64) {}
+
+    fn count(&self) -> u64 {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_dial_rotation, ClicksThroughZero, DirectionReversals, LandingsOnZero, TargetPasses};
+
+    #[test]
+    fn clamp_stops_at_the_limit() {
+        let mut dial = BoundedDial::new(100, 90, OverflowPolicy::Clamp).unwrap();
+
+        let movement = dial.turn(&get_dial_rotation("R25")).unwrap();
+
+        assert_eq!(movement.end, 99);
+        assert_eq!(movement.stop_hits, 1);
+        dial.turn(&get_dial_rotation("L300")).unwrap();
+        assert_eq!(dial.get_current_position(), 0);
+        assert_eq!(dial.get_stop_hits(), 2);
+    }
+
+    #[test]
+    fn reaching_the_limit_exactly_is_not_a_stop_hit() {
+        let mut dial = BoundedDial::new(100, 90, OverflowPolicy::Error).unwrap();
+
+        dial.turn(&get_dial_rotation("R9")).unwrap();
+
+        assert_eq!(dial.get_current_position(), 99);
+        assert_eq!(dial.get_stop_hits(), 0);
+    }
+
+    #[test]
+    fn error_policy_leaves_the_dial_alone() {
+        let mut dial = BoundedDial::new(100, 10, OverflowPolicy::Error).unwrap();
+
+        assert!(dial.turn(&get_dial_rotation("L11")).is_err());
+        assert_eq!(dial.get_current_position(), 10);
+        assert_eq!(dial.get_stop_hits(), 0);
+    }
+
+    #[test]
+    fn reflect_bounces_between_the_stops() {
+        let mut dial = BoundedDial::new(10, 5, OverflowPolicy::Reflect).unwrap();
+
+        // 5 -> 9 (4 clicks), bounce, 9 -> 0 (9 clicks), bounce, 0 -> 7 (7 clicks).
+        let movement = dial.turn(&get_dial_rotation("R20")).unwrap();
+
+        assert_eq!(movement.end, 7);
+        assert_eq!(movement.stop_hits, 2);
+    }
+
+    #[test]
+    fn counting_rules_run_on_bounded_dials() {
+        let mut dial = BoundedDial::new(10, 5, OverflowPolicy::Reflect).unwrap();
+        let mut clicks = ClicksThroughZero::default();
+        let mut landings = LandingsOnZero::default();
+        let mut reversals = DirectionReversals::default();
+
+        for text in ["R20", "L7", "L3"] {
+            let rotation = get_dial_rotation(text);
+            dial.clone().turn_with(&rotation, &mut landings).unwrap();
+            dial.clone().turn_with(&rotation, &mut reversals).unwrap();
+            dial.turn_with(&rotation, &mut clicks).unwrap();
+        }
+
+        // R20 bounces off 0 once and L7 lands on it from 7, so 0 is clicked twice. Only L7 finishes
+        // there; L3 bounces off 0 to end on 3. The rotations run R, L, L, so one reversal.
+        assert_eq!(dial.get_current_position(), 3);
+        assert_eq!(clicks.count(), 2);
+        assert_eq!(landings.count(), 1);
+        assert_eq!(reversals.count(), 1);
+    }
+
+    #[test]
+    fn bouncing_off_zero_is_not_a_landing_or_a_reversal() {
+        let mut dial = BoundedDial::new(10, 5, OverflowPolicy::Reflect).unwrap();
+        let mut landings = LandingsOnZero::default();
+        let mut reversals = DirectionReversals::default();
+
+        dial.clone().turn_with(&get_dial_rotation("L10"), &mut reversals).unwrap();
+        let movement = dial.turn_with(&get_dial_rotation("L10"), &mut landings).unwrap();
+
+        assert_eq!((movement.end, movement.stop_hits), (5, 1));
+        assert_eq!((landings.count(), reversals.count()), (0, 0));
+    }
+
+    /// The bounce one leg at a time, to check the arithmetic in `turn_with` against.
+    fn walk_legs<P: CountingPolicy>(size: i32, start: i32, rotation: &DialRotation, policy: &mut P) -> (i32, u64) {
+        let (mut position, mut direction, mut remaining, mut stop_hits) = (start, rotation.get_direction(), rotation.get_value(), 0);
+        while remaining > 0 {
+            let available = if direction == Direction::Left { position } else { size - 1 - position };
+            if available == 0 {
+                stop_hits += 1;
+                if size == 1 {
+                    break;
+                }
+                direction = direction.opposite();
+                continue;
+            }
+            let movement = DialMovement::new(size, position, DialRotation::new(direction, available.min(remaining)));
+            policy.observe(&movement);
+            position = movement.end;
+            remaining -= movement.rotation.get_value();
+        }
+        (position, stop_hits)
+    }
+
+    #[test]
+    fn reflect_matches_walking_each_leg() {
+        for size in 1..=5 {
+            for start in 0..size {
+                for amount in 0..=40 {
+                    for direction in [Direction::Left, Direction::Right] {
+                        let rotation = DialRotation::new(direction, amount);
+                        let (mut expected_clicks, mut expected_targets) = (ClicksThroughZero::default(), TargetPasses::new([1, 3]));
+                        let expected = walk_legs(size, start, &rotation, &mut expected_clicks);
+                        walk_legs(size, start, &rotation, &mut expected_targets);
+
+                        let (mut clicks, mut targets, mut landings) = (ClicksThroughZero::default(), TargetPasses::new([1, 3]), LandingsOnZero::default());
+                        let mut dial = BoundedDial::new(size, start, OverflowPolicy::Reflect).unwrap();
+                        dial.clone().turn_with(&rotation, &mut targets).unwrap();
+                        dial.clone().turn_with(&rotation, &mut landings).unwrap();
+                        let movement = dial.turn_with(&rotation, &mut clicks).unwrap();
+
+                        let context = format!("size {} from {}: {}", size, start, rotation);
+                        assert_eq!((movement.end, movement.stop_hits), expected, "{}", context);
+                        assert_eq!(clicks.count(), expected_clicks.count(), "{}", context);
+                        assert_eq!(targets.count(), expected_targets.count(), "{}", context);
+                        assert_eq!(landings.count(), (expected.0 == 0) as u64, "{}", context);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reflecting_i32_max_clicks_on_a_tiny_dial_is_immediate() {
+        let mut dial = BoundedDial::new(2, 0, OverflowPolicy::Reflect).unwrap();
+        let mut clicks = ClicksThroughZero::default();
+
+        let movement = dial.turn_with(&DialRotation::new(Direction::Right, i32::MAX), &mut clicks).unwrap();
+
+        // 0 -> 1, then 2^31 - 2 one-click sweeps, every other one arriving at 0.
+        assert_eq!((movement.end, movement.stop_hits), (1, i32::MAX as u64 - 1));
+        assert_eq!(clicks.count(), (i32::MAX as u64 - 1) / 2);
+
+        let movement = dial.turn(&DialRotation::new(Direction::Right, i32::MAX)).unwrap();
+        assert_eq!((movement.end, movement.stop_hits), (0, i32::MAX as u64));
+    }
+
+    #[test]
+    fn single_position_dial_only_hits_its_stop() {
+        let mut dial = BoundedDial::new(1, 0, OverflowPolicy::Reflect).unwrap();
+
+        let movement = dial.turn(&get_dial_rotation("R5")).unwrap();
+
+        assert_eq!((movement.end, movement.stop_hits), (0, 1));
+    }
+}
@@ -0,0 +1,345 @@
+use crate::{DialInt, DialMovement, Direction};
+use std::fmt;
+use std::str::FromStr;
+
+/// Something to count while a dial turns. `Dial::turn_dial_with` moves the dial and then hands
+/// each `DialMovement` to the policy. `T` is the width of the dial's rotation amounts; policies
+/// whose count cannot outgrow a `u64` at any width are implemented for all of them, with an
+/// inherent `count` so reading it does not need the width spelled out.
+pub trait CountingPolicy<T: DialInt = i32> {
+    fn observe(&mut self, movement: &DialMovement<T>);
+
+    /// Observes `movements` in order, `times` times over. Policies that can work out what the
+    /// repeats add without replaying each one should override this.
+    fn observe_repeated(&mut self, movements: &[DialMovement<T>], times: u64) {
+        for _ in 0..times {
+            for movement in movements {
+                self.observe(movement);
+            }
+        }
+    }
+
+    /// Whether the count only looks at each rotation as a whole: where it ends and which way it
+    /// was asked to turn. A `BoundedDial` hands these policies the rotation instead of its legs.
+    fn judges_whole_rotations(&self) -> bool {
+        false
+    }
+
+    fn count(&self) -> u64;
+}
+
+/// The two puzzle rules. Both only depend on where each rotation starts, so a whole run's count
+/// is the sum of its rotations' counts, which lets analyses combine partial runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CountingRule {
+    LandingsOnZero,
+    ClicksThroughZero,
+}
+
+impl CountingRule {
+    pub const ALL: [CountingRule; 2] = [CountingRule::LandingsOnZero, CountingRule::ClicksThroughZero];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CountingRule::LandingsOnZero => "landings",
+            CountingRule::ClicksThroughZero => "clicks",
+        }
+    }
+
+    pub fn count_movement(self, movement: &DialMovement) -> u64 {
+        match self {
+            CountingRule::LandingsOnZero => movement.landed_on_zero() as u64,
+            CountingRule::ClicksThroughZero => movement.zero_clicks(),
+        }
+    }
+}
+
+impl fmt::Display for CountingRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for CountingRule {
+    type Err = String;
+
+    /// Accepts the rule names as well as `part1` and `part2`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "landings" | "part1" => Ok(CountingRule::LandingsOnZero),
+            "clicks" | "part2" => Ok(CountingRule::ClicksThroughZero),
+            _ => Err(format!("Unknown counting rule {:?} (expected landings, clicks, part1 or part2)", s)),
+        }
+    }
+}
+
+/// Part 1: counts rotations that finish on 0.
+#[derive(Debug, Clone, Default)]
+pub struct LandingsOnZero {
+    count: u64,
+}
+
+impl LandingsOnZero {
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl<T: DialInt> CountingPolicy<T> for LandingsOnZero {
+    fn observe(&mut self, movement: &DialMovement<T>) {
+        self.count += movement.landed_on_zero() as u64;
+    }
+
+    fn observe_repeated(&mut self, movements: &[DialMovement<T>], times: u64) {
+        self.count += times * movements.iter().filter(|movement| movement.landed_on_zero()).count() as u64;
+    }
+
+    fn judges_whole_rotations(&self) -> bool {
+        true
+    }
+
+    fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Part 2: counts every click that lands on 0, including the last one of a rotation.
+#[derive(Debug, Clone, Default)]
+pub struct ClicksThroughZero {
+    count: u64,
+}
+
+impl CountingPolicy for ClicksThroughZero {
+    fn observe(&mut self, movement: &DialMovement) {
+        self.count += CountingRule::ClicksThroughZero.count_movement(movement);
+    }
+
+    fn observe_repeated(&mut self, movements: &[DialMovement], times: u64) {
+        self.count += times * movements.iter().map(|movement| CountingRule::ClicksThroughZero.count_movement(movement)).sum::<u64>();
+    }
+
+    fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Counts every click that lands on any of the target positions.
+#[derive(Debug, Clone)]
+pub struct TargetPasses {
+    targets: Vec<i32>,
+    count: u64,
+}
+
+impl TargetPasses {
+    pub fn new<T: IntoIterator<Item = i32>>(targets: T) -> TargetPasses {
+        let mut targets = targets.into_iter().collect::<Vec<_>>();
+        targets.sort_unstable();
+        targets.dedup();
+        TargetPasses { targets, count: 0 }
+    }
+
+    pub fn targets(&self) -> &[i32] {
+        &self.targets
+    }
+}
+
+impl CountingPolicy for TargetPasses {
+    fn observe(&mut self, movement: &DialMovement) {
+        for &target in &self.targets {
+            if (0..movement.size).contains(&target) {
+                self.count += movement.target_clicks(target);
+            }
+        }
+    }
+
+    fn observe_repeated(&mut self, movements: &[DialMovement], times: u64) {
+        let before = self.count;
+        movements.iter().for_each(|movement| self.observe(movement));
+        self.count = before + times * (self.count - before);
+    }
+
+    fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Counts how often a rotation turns the other way from the last one that moved the dial.
+#[derive(Debug, Clone, Default)]
+pub struct DirectionReversals {
+    last_direction: Option<Direction>,
+    count: u64,
+}
+
+impl DirectionReversals {
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl<T: DialInt> CountingPolicy<T> for DirectionReversals {
+    fn observe(&mut self, movement: &DialMovement<T>) {
+        if movement.rotation.get_value() == T::default() {
+            return;
+        }
+
+        let direction = movement.rotation.get_direction();
+        if self.last_direction.is_some_and(|last| last != direction) {
+            self.count += 1;
+        }
+        self.last_direction = Some(direction);
+    }
+
+    /// Only the first repeat depends on the direction before it; every later one adds the same.
+    fn observe_repeated(&mut self, movements: &[DialMovement<T>], times: u64) {
+        if times == 0 {
+            return;
+        }
+        movements.iter().for_each(|movement| self.observe(movement));
+        if times > 1 {
+            let before = self.count;
+            movements.iter().for_each(|movement| self.observe(movement));
+            self.count += (times - 2) * (self.count - before);
+        }
+    }
+
+    fn judges_whole_rotations(&self) -> bool {
+        true
+    }
+
+    fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Records the furthest the dial has been, in clicks, from where it started. Distance is
+/// measured without wrapping, so a full turn right is 100 clicks away on a standard dial.
+#[derive(Debug, Clone, Default)]
+pub struct MaxDistanceFromStart {
+    offset: i64,
+    max_distance: u64,
+}
+
+impl MaxDistanceFromStart {
+    pub fn offset(&self) -> i64 {
+        self.offset
+    }
+}
+
+impl CountingPolicy for MaxDistanceFromStart {
+    fn observe(&mut self, movement: &DialMovement) {
+        // Each rotation only turns one way, so the furthest point of a rotation is its end.
+        self.offset += movement.rotation.get_signed_value();
+        self.max_distance = self.max_distance.max(self.offset.unsigned_abs());
+    }
+
+    /// Each repeat shifts the offset by the same amount, so the furthest point is in the first
+    /// or the last repeat and the ones in between can be skipped.
+    fn observe_repeated(&mut self, movements: &[DialMovement], times: u64) {
+        if times == 0 {
+            return;
+        }
+        let start = self.offset;
+        movements.iter().for_each(|movement| self.observe(movement));
+        if times > 1 {
+            self.offset += (times - 2) as i64 * (self.offset - start);
+            movements.iter().for_each(|movement| self.observe(movement));
+        }
+    }
+
+    fn count(&self) -> u64 {
+        self.max_distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_dial_rotation, Dial, DialRotation, TEST_INPUT};
+
+    fn run<P: CountingPolicy>(start: i32, rotations: &[&str], mut policy: P) -> u64 {
+        let mut dial = Dial::new(start, 0).unwrap();
+        for rotation in rotations {
+            dial.turn_dial_with(&get_dial_rotation(rotation), &mut policy);
+        }
+        policy.count()
+    }
+
+    #[test]
+    fn counting_rules_parse_their_names_and_part_numbers() {
+        for rule in CountingRule::ALL {
+            assert_eq!(rule.to_string().parse::<CountingRule>(), Ok(rule));
+        }
+        assert_eq!("part2".parse::<CountingRule>(), Ok(CountingRule::ClicksThroughZero));
+        assert!("part3".parse::<CountingRule>().is_err());
+    }
+
+    #[test]
+    fn landings_on_zero_gives_3_for_test_input() {
+        assert_eq!(run(50, &TEST_INPUT, LandingsOnZero::default()), 3);
+    }
+
+    #[test]
+    fn clicks_through_zero_gives_6_for_test_input() {
+        assert_eq!(run(50, &TEST_INPUT, ClicksThroughZero::default()), 6);
+    }
+
+    #[test]
+    fn target_passes_of_only_zero_matches_clicks_through_zero() {
+        assert_eq!(run(50, &TEST_INPUT, TargetPasses::new([0])), 6);
+    }
+
+    #[test]
+    fn target_passes_counts_each_target_once_per_click() {
+        // R250 from 10 clicks through 20 and 90 three and two times.
+        assert_eq!(run(10, &["R250"], TargetPasses::new([20, 90, 20])), 5);
+    }
+
+    #[test]
+    fn direction_reversals_ignores_repeats_and_zero_amounts() {
+        assert_eq!(run(50, &["L1", "L2", "R0", "R3", "L4", "L5", "R6"], DirectionReversals::default()), 3);
+    }
+
+    #[test]
+    fn max_distance_from_start_measures_unwrapped_offset() {
+        assert_eq!(run(50, &["R30", "L250", "R400"], MaxDistanceFromStart::default()), 220);
+    }
+
+    fn assert_repeats_match_looping<P: CountingPolicy + Clone>(policy: P) {
+        let mut dial = Dial::with_size(40, 7, 0).unwrap();
+        let movements = ["R30", "L250", "R0", "L5", "R400"].map(|rotation| dial.rotate(&get_dial_rotation(rotation)));
+
+        // Start each policy part way through a run so its state before the repeats matters.
+        let mut policy = policy;
+        dial.turn_dial_with(&get_dial_rotation("L3"), &mut policy);
+
+        for times in 0..6 {
+            let mut repeated = policy.clone();
+            let mut looped = policy.clone();
+            repeated.observe_repeated(&movements, times);
+            for _ in 0..times {
+                movements.iter().for_each(|movement| looped.observe(movement));
+            }
+            assert_eq!(repeated.count(), looped.count(), "{} times", times);
+        }
+    }
+
+    #[test]
+    fn observing_repeats_matches_observing_each_one() {
+        assert_repeats_match_looping(LandingsOnZero::default());
+        assert_repeats_match_looping(ClicksThroughZero::default());
+        assert_repeats_match_looping(TargetPasses::new([0, 13]));
+        assert_repeats_match_looping(DirectionReversals::default());
+        assert_repeats_match_looping(MaxDistanceFromStart::default());
+    }
+
+    #[test]
+    fn turning_dial_with_policy_returns_the_movement() {
+        let mut dial = Dial::new(95, 0).unwrap();
+        let mut policy = LandingsOnZero::default();
+
+        let movement = dial.turn_dial_with(&get_dial_rotation("R5"), &mut policy);
+
+        assert_eq!(movement, DialMovement { size: 100, start: 95, rotation: DialRotation::new(Direction::Right, 5), end: 0 });
+        assert_eq!(policy.count(), 1);
+    }
+}
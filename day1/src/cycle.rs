@@ -0,0 +1,168 @@
+use crate::{CountingRule, Dial, DialRotation, RotationSummary};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepeatedOutcome {
+    pub end_position: i32,
+    pub zero_landings: u128,
+    pub zero_clicks: u128,
+}
+
+impl RepeatedOutcome {
+    pub fn count(&self, rule: CountingRule) -> u128 {
+        match rule {
+            CountingRule::LandingsOnZero => self.zero_landings,
+            CountingRule::ClicksThroughZero => self.zero_clicks,
+        }
+    }
+}
+
+/// Where a dial's start positions go when a program is replayed: `tail_len` replays before the
+/// first repeated start, then a cycle of `cycle_len` starts that repeats forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayCycle {
+    pub tail_len: usize,
+    pub cycle_len: usize,
+}
+
+/// A rotation file that gets replayed back to back. One replay is summarised for every start
+/// position, so each replay costs a lookup and any number of replays costs at most one walk
+/// round the cycle of start positions.
+pub struct RepeatedProgram {
+    summary: RotationSummary,
+}
+
+impl RepeatedProgram {
+    pub fn new(size: i32, rotations: &[DialRotation]) -> Result<RepeatedProgram, String> {
+        Ok(RepeatedProgram { summary: RotationSummary::from_rotations(size, rotations)? })
+    }
+
+    pub fn summary(&self) -> &RotationSummary {
+        &self.summary
+    }
+
+    pub fn cycle_from(&self, start: i32) -> Result<ReplayCycle, String> {
+        if !(0..self.summary.get_size()).contains(&start) {
+            return Err("Invalid dial starting position".to_string());
+        }
+
+        let mut first_seen = vec![None; self.summary.get_size() as usize];
+        let mut position = start;
+        for replay in 0.. {
+            if let Some(tail_len) = first_seen[position as usize] {
+                return Ok(ReplayCycle { tail_len, cycle_len: replay - tail_len });
+            }
+            first_seen[position as usize] = Some(replay);
+            position = self.summary.end_position(position);
+        }
+        unreachable!()
+    }
+
+    /// Replays the program `repetitions` times starting from `dial`'s position.
+    pub fn evaluate(&self, dial: &Dial, repetitions: u64) -> Result<RepeatedOutcome, String> {
+        if dial.get_size() != self.summary.get_size() {
+            return Err(format!("Dial of size {} does not match program for size {}", dial.get_size(), self.summary.get_size()));
+        }
+
+        let start = dial.get_current_position();
+        let cycle = self.cycle_from(start)?;
+
+        // Starts and running counts for every replay up to the end of the first cycle.
+        let mut outcomes = vec![RepeatedOutcome { end_position: start, zero_landings: 0, zero_clicks: 0 }];
+        for _ in 0..cycle.tail_len + cycle.cycle_len {
+            let previous = *outcomes.last().unwrap();
+            outcomes.push(RepeatedOutcome {
+                end_position: self.summary.end_position(previous.end_position),
+                zero_landings: previous.zero_landings + self.summary.count(CountingRule::LandingsOnZero, previous.end_position) as u128,
+                zero_clicks: previous.zero_clicks + self.summary.count(CountingRule::ClicksThroughZero, previous.end_position) as u128,
+            });
+        }
+
+        if repetitions < outcomes.len() as u64 {
+            return Ok(outcomes[repetitions as usize]);
+        }
+
+        let cycle_start = outcomes[cycle.tail_len];
+        let cycle_end = outcomes[cycle.tail_len + cycle.cycle_len];
+        let replays_after_tail = repetitions - cycle.tail_len as u64;
+        let full_cycles = (replays_after_tail / cycle.cycle_len as u64) as u128;
+        let partial = outcomes[cycle.tail_len + (replays_after_tail % cycle.cycle_len as u64) as usize];
+
+        Ok(RepeatedOutcome {
+            end_position: partial.end_position,
+            zero_landings: partial.zero_landings + full_cycles * (cycle_end.zero_landings - cycle_start.zero_landings),
+            zero_clicks: partial.zero_clicks + full_cycles * (cycle_end.zero_clicks - cycle_start.zero_clicks),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_dial_rotation, test_rotations};
+
+    #[test]
+    fn repeated_evaluation_matches_replaying_the_dial() {
+        let rotations = test_rotations();
+        let program = RepeatedProgram::new(100, &rotations).unwrap();
+        let mut part1 = Dial::new(50, 0).unwrap();
+        let mut part2 = Dial::new(50, 0).unwrap();
+
+        for repetitions in 0..120u64 {
+            let outcome = program.evaluate(&Dial::new(50, 0).unwrap(), repetitions).unwrap();
+
+            assert_eq!(outcome.end_position, part2.get_current_position(), "{} repetitions", repetitions);
+            assert_eq!(outcome.zero_landings, part1.get_zero_count() as u128, "{} repetitions", repetitions);
+            assert_eq!(outcome.zero_clicks, part2.get_zero_count() as u128, "{} repetitions", repetitions);
+
+            for rotation in &rotations {
+                part1.turn_dial_in_direction_part1(rotation).unwrap();
+                part2.turn_dial_in_direction_part2(rotation).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_input_cycles_through_50_starts() {
+        // Each replay moves the dial 82 clicks left, i.e. 18 right, and gcd(18, 100) = 2.
+        let program = RepeatedProgram::new(100, &test_rotations()).unwrap();
+
+        assert_eq!(program.cycle_from(50), Ok(ReplayCycle { tail_len: 0, cycle_len: 50 }));
+    }
+
+    #[test]
+    fn huge_repetition_counts_are_exact() {
+        let program = RepeatedProgram::new(100, &[get_dial_rotation("R100"), get_dial_rotation("L50")]).unwrap();
+
+        let outcome = program.evaluate(&Dial::new(50, 0).unwrap(), 1_000_000_000_000_000_000).unwrap();
+
+        // Replays alternate between starting on 50 (R100 passes 0, L50 lands on it) and starting
+        // on 0 (R100 lands back on 0), so each pair of replays clicks through 0 three times.
+        assert_eq!(outcome.zero_clicks, 1_500_000_000_000_000_000);
+        assert_eq!(outcome.zero_landings, 1_000_000_000_000_000_000);
+        assert_eq!(outcome.end_position, 50);
+    }
+
+    #[test]
+    fn counts_beyond_u64_use_u128() {
+        let program = RepeatedProgram::new(1, &[get_dial_rotation("R1000000000")]).unwrap();
+
+        let outcome = program.evaluate(&Dial::with_size(1, 0, 0).unwrap(), 1_000_000_000_000_000_000).unwrap();
+
+        assert_eq!(outcome.zero_clicks, 1_000_000_000_000_000_000_000_000_000);
+    }
+
+    #[test]
+    fn dial_of_wrong_size_is_an_error() {
+        let program = RepeatedProgram::new(100, &test_rotations()).unwrap();
+
+        assert!(program.evaluate(&Dial::with_size(12, 0, 0).unwrap(), 3).is_err());
+    }
+
+    #[test]
+    fn cycle_from_invalid_start_is_an_error() {
+        let program = RepeatedProgram::new(100, &test_rotations()).unwrap();
+
+        assert!(program.cycle_from(100).is_err());
+        assert!(program.cycle_from(-1).is_err());
+    }
+}
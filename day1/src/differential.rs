@@ -0,0 +1,321 @@
+use crate::{Dial, DialRotation, Direction, GeneratorRng, ReferenceDial};
+use std::fmt;
+
+/// A run of rotations on a dial of `size` positions starting at `start`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DifferentialCase {
+    pub size: i32,
+    pub start: i32,
+    pub rotations: Vec<DialRotation>,
+}
+
+impl fmt::Display for DifferentialCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "size {}, start {}:", self.size, self.start)?;
+        for rotation in &self.rotations {
+            write!(f, " {}", rotation)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where a dial is and what it has counted after some rotations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DialState {
+    pub position: i32,
+    pub zero_landings: u64,
+    pub zero_clicks: u64,
+}
+
+impl fmt::Display for DialState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "position {}, {} landings on 0, {} clicks through 0", self.position, self.zero_landings, self.zero_clicks)
+    }
+}
+
+/// The state after each rotation according to `ReferenceDial`.
+pub fn reference_states(case: &DifferentialCase) -> Vec<DialState> {
+    let mut dial = ReferenceDial::new(case.size, case.start).unwrap();
+    case.rotations
+        .iter()
+        .map(|rotation| {
+            dial.turn(rotation);
+            DialState { position: dial.position(), zero_landings: dial.zero_landings(), zero_clicks: dial.zero_clicks() }
+        })
+        .collect()
+}
+
+/// The state after each rotation according to `Dial`, with one dial per part. The dials count
+/// in `i64`, which `i32` amounts cannot overflow in any case short enough to check.
+pub fn dial_states(case: &DifferentialCase) -> Vec<DialState> {
+    let mut part1 = Dial::<i64>::with_size(case.size, case.start, 0).unwrap();
+    let mut part2 = Dial::<i64>::with_size(case.size, case.start, 0).unwrap();
+    case.rotations
+        .iter()
+        .map(|rotation| {
+            let rotation = DialRotation::new(rotation.get_direction(), rotation.get_value() as i64);
+            part1.turn_dial_in_direction_part1(&rotation).unwrap();
+            part2.turn_dial_in_direction_part2(&rotation).unwrap();
+            DialState {
+                position: part2.get_current_position(),
+                zero_landings: part1.get_zero_count() as u64,
+                zero_clicks: part2.get_zero_count() as u64,
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialMismatch {
+    pub case: DifferentialCase,
+    /// How many rotations in the states first differed.
+    pub step: usize,
+    pub expected: DialState,
+    pub actual: DialState,
+}
+
+impl fmt::Display for DialMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.case)?;
+        writeln!(f, "after rotation {} ({}):", self.step, self.case.rotations[self.step - 1])?;
+        writeln!(f, "  reference: {}", self.expected)?;
+        write!(f, "  tested:    {}", self.actual)
+    }
+}
+
+/// Runs `case` through the reference and `implementation`, returning the first step they disagree on.
+pub fn find_mismatch<F>(case: &DifferentialCase, implementation: F) -> Option<DialMismatch>
+where
+    F: Fn(&DifferentialCase) -> Vec<DialState>,
+{
+    let expected = reference_states(case);
+    let actual = implementation(case);
+    assert_eq!(actual.len(), expected.len(), "implementation must report one state per rotation");
+
+    let step = expected.iter().zip(&actual).position(|(expected, actual)| expected != actual)?;
+    Some(DialMismatch { case: case.clone(), step: step + 1, expected: expected[step], actual: actual[step] })
+}
+
+/// Greedily simplifies a failing case, dropping rotations and making amounts, the start and the
+/// size smaller for as long as it still fails.
+pub fn shrink_mismatch<F>(mismatch: DialMismatch, implementation: F) -> DialMismatch
+where
+    F: Fn(&DifferentialCase) -> Vec<DialState>,
+{
+    let mut smallest = mismatch;
+    'shrinking: loop {
+        // Only the rotations up to the failing step matter.
+        let case = DifferentialCase { rotations: smallest.case.rotations[..smallest.step].to_vec(), ..smallest.case.clone() };
+        for candidate in smaller_cases(&case) {
+            if let Some(mismatch) = find_mismatch(&candidate, &implementation) {
+                smallest = mismatch;
+                continue 'shrinking;
+            }
+        }
+        if case != smallest.case {
+            smallest = find_mismatch(&case, &implementation).unwrap();
+        }
+        return smallest;
+    }
+}
+
+/// Every case one simplification away from `case`, most aggressive first.
+fn smaller_cases(case: &DifferentialCase) -> Vec<DifferentialCase> {
+    let mut candidates = Vec::new();
+
+    for index in 0..case.rotations.len() {
+        let mut rotations = case.rotations.clone();
+        rotations.remove(index);
+        candidates.push(DifferentialCase { rotations, ..case.clone() });
+    }
+
+    for (index, rotation) in case.rotations.iter().enumerate() {
+        let amount = rotation.get_value();
+        let mut amounts = vec![0, amount / 2, amount - case.size, amount - 1];
+        amounts.retain(|&smaller| (0..amount).contains(&smaller));
+        amounts.dedup();
+        for smaller in amounts {
+            let mut rotations = case.rotations.clone();
+            rotations[index] = DialRotation::new(rotation.get_direction(), smaller);
+            candidates.push(DifferentialCase { rotations, ..case.clone() });
+        }
+        if rotation.get_direction() == Direction::Right {
+            let mut rotations = case.rotations.clone();
+            rotations[index] = DialRotation::new(Direction::Left, amount);
+            candidates.push(DifferentialCase { rotations, ..case.clone() });
+        }
+    }
+
+    for start in [0, case.start / 2, case.start - 1] {
+        if (0..case.start).contains(&start) {
+            candidates.push(DifferentialCase { start, ..case.clone() });
+        }
+    }
+
+    for size in [1, case.size / 2, case.size - 1] {
+        if (1..case.size).contains(&size) {
+            candidates.push(DifferentialCase { size, start: case.start % size, ..case.clone() });
+        }
+    }
+    candidates
+}
+
+/// Checks every case, stopping at the first mismatch and shrinking it. Returns how many cases
+/// were checked when they all agree.
+pub fn run_differential<I, F>(cases: I, implementation: F) -> Result<usize, DialMismatch>
+where
+    I: IntoIterator<Item = DifferentialCase>,
+    F: Fn(&DifferentialCase) -> Vec<DialState>,
+{
+    let mut checked = 0;
+    for case in cases {
+        if let Some(mismatch) = find_mismatch(&case, &implementation) {
+            return Err(shrink_mismatch(mismatch, &implementation));
+        }
+        checked += 1;
+    }
+    Ok(checked)
+}
+
+/// Random cases biased towards the awkward ones: tiny dials, starts and landings on 0, and
+/// amounts close to whole turns as well as very large ones.
+pub fn random_cases(seed: u64, count: usize) -> impl Iterator<Item = DifferentialCase> {
+    let mut rng = GeneratorRng::new(seed);
+
+    (0..count).map(move |_| {
+        let size = if rng.chance(0.8) { 1 + rng.below(10) as i32 } else { 100 };
+        let start = if rng.chance(0.3) { 0 } else { rng.below(size as u64) as i32 };
+        let mut position = start as i64;
+
+        let rotations = (0..1 + rng.below(8))
+            .map(|_| {
+                let direction = if rng.chance(0.5) { Direction::Left } else { Direction::Right };
+                let amount = match rng.below(4) {
+                    0 => rng.below(3 * size as u64 + 1) as i64,
+                    1 => rng.below(10_000) as i64,
+                    2 => rng.below(4) as i64 * size as i64 + rng.below(3) as i64 - 1,
+                    _ => {
+                        let to_zero = match direction {
+                            Direction::Right => (size as i64 - position) % size as i64,
+                            Direction::Left => position,
+                        };
+                        to_zero + rng.below(3) as i64 * size as i64
+                    },
+                };
+                let rotation = DialRotation::new(direction, amount.max(0) as i32);
+                position = (position + rotation.get_signed_value()).rem_euclid(size as i64);
+                rotation
+            })
+            .collect();
+
+        DifferentialCase { size, start, rotations }
+    })
+}
+
+/// Every case with a dial of up to `max_size` positions, any start, and up to `max_rotations`
+/// rotations of up to `max_amount` clicks either way.
+pub fn exhaustive_cases(max_size: i32, max_rotations: usize, max_amount: i32) -> impl Iterator<Item = DifferentialCase> {
+    let single_rotations = (0..=max_amount)
+        .flat_map(|amount| [DialRotation::new(Direction::Left, amount), DialRotation::new(Direction::Right, amount)])
+        .collect::<Vec<_>>();
+
+    let mut sequences = vec![Vec::new()];
+    let mut frontier = vec![Vec::new()];
+    for _ in 0..max_rotations {
+        frontier = frontier
+            .iter()
+            .flat_map(|sequence: &Vec<DialRotation>| {
+                single_rotations.iter().map(move |rotation| {
+                    let mut longer = sequence.clone();
+                    longer.push(*rotation);
+                    longer
+                })
+            })
+            .collect();
+        sequences.extend(frontier.iter().cloned());
+    }
+    sequences.remove(0);
+
+    (1..=max_size).flat_map(move |size| {
+        let sequences = sequences.clone();
+        (0..size).flat_map(move |start| {
+            sequences.clone().into_iter().map(move |rotations| DifferentialCase { size, start, rotations })
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_dial_rotation;
+
+    /// The classic part 2 mistake: counting the 0 a left turn starts from.
+    fn counts_leaving_zero(case: &DifferentialCase) -> Vec<DialState> {
+        let mut position = case.start;
+        let mut extra = 0;
+        let mut states = reference_states(case);
+        for (rotation, state) in case.rotations.iter().zip(&mut states) {
+            if position == 0 && rotation.get_direction() == Direction::Left && rotation.get_value() > 0 {
+                extra += 1;
+            }
+            position = state.position;
+            state.zero_clicks += extra;
+        }
+        states
+    }
+
+    #[test]
+    fn dial_agrees_with_reference_on_random_cases() {
+        if let Err(mismatch) = run_differential(random_cases(2025, 5000), dial_states) {
+            panic!("{}", mismatch);
+        }
+    }
+
+    #[test]
+    fn dial_agrees_with_reference_on_every_small_case() {
+        match run_differential(exhaustive_cases(5, 3, 7), dial_states) {
+            Ok(checked) => assert_eq!(checked, (1 + 2 + 3 + 4 + 5) * (16 + 16 * 16 + 16 * 16 * 16)),
+            Err(mismatch) => panic!("{}", mismatch),
+        }
+    }
+
+    #[test]
+    fn mismatches_shrink_to_a_minimal_case() {
+        let mismatch = run_differential(random_cases(1, 1000), counts_leaving_zero).unwrap_err();
+
+        assert_eq!(mismatch.case, DifferentialCase { size: 1, start: 0, rotations: vec![get_dial_rotation("L1")] });
+        assert_eq!(mismatch.step, 1);
+        assert_eq!(mismatch.expected, DialState { position: 0, zero_landings: 1, zero_clicks: 1 });
+        assert_eq!(mismatch.actual.zero_clicks, 2);
+    }
+
+    #[test]
+    fn shrinking_drops_rotations_after_the_failure() {
+        let case = DifferentialCase {
+            size: 100,
+            start: 50,
+            rotations: ["R30", "L80", "L250", "R7"].map(get_dial_rotation).to_vec(),
+        };
+        let mismatch = find_mismatch(&case, counts_leaving_zero).unwrap();
+        assert_eq!(mismatch.step, 3);
+
+        let shrunk = shrink_mismatch(mismatch, counts_leaving_zero);
+
+        assert_eq!(shrunk.case.to_string(), "size 1, start 0: L1");
+    }
+
+    #[test]
+    fn mismatch_prints_the_case_and_both_states() {
+        let case = DifferentialCase { size: 1, start: 0, rotations: vec![get_dial_rotation("L1")] };
+
+        let text = find_mismatch(&case, counts_leaving_zero).unwrap().to_string();
+
+        assert_eq!(
+            text,
+            "size 1, start 0: L1\n\
+             after rotation 1 (L1):\n  \
+             reference: position 0, 1 landings on 0, 1 clicks through 0\n  \
+             tested:    position 0, 1 landings on 0, 2 clicks through 0"
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use crate::{DialMovement, DialStep, VisitDifferences};
+use std::io::{self, Write};
+
+const SVG_WIDTH: f64 = 800.0;
+const SVG_HEIGHT: f64 = 320.0;
+const SVG_MARGIN: f64 = 40.0;
+
+/// Buckets of equal runs of steps. When another bucket is needed and there are already
+/// `max_buckets`, neighbouring buckets are merged and the run length doubles, so any number of
+/// steps fits in bounded memory.
+#[derive(Debug, Clone)]
+struct Buckets<T> {
+    max_buckets: usize,
+    bucket_width: u64,
+    steps: u64,
+    buckets: Vec<T>,
+}
+
+impl<T> Buckets<T> {
+    fn new(max_buckets: usize) -> Buckets<T> {
+        assert!(max_buckets >= 2, "need at least 2 buckets to downsample into");
+        Buckets { max_buckets, bucket_width: 1, steps: 0, buckets: Vec::new() }
+    }
+
+    /// The bucket for the next step, starting from `empty` if it is the first step in it.
+    fn next(&mut self, empty: impl Fn() -> T, merge: impl Fn(T, T) -> T) -> &mut T {
+        if self.steps.is_multiple_of(self.bucket_width) && self.buckets.len() == self.max_buckets {
+            let mut merged = Vec::with_capacity(self.max_buckets);
+            let mut buckets = self.buckets.drain(..);
+            while let Some(first) = buckets.next() {
+                merged.push(match buckets.next() {
+                    Some(second) => merge(first, second),
+                    None => first,
+                });
+            }
+            drop(buckets);
+            self.buckets = merged;
+            self.bucket_width *= 2;
+        }
+
+        let index = (self.steps / self.bucket_width) as usize;
+        if index == self.buckets.len() {
+            self.buckets.push(empty());
+        }
+        self.steps += 1;
+        &mut self.buckets[index]
+    }
+}
+
+/// The steps of a trajectory in one bucket of a `TrajectorySamples`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleBucket {
+    pub min_position: i32,
+    pub max_position: i32,
+    pub end_position: i32,
+    pub zero_clicks: u64,
+    pub zero_landings: u64,
+}
+
+impl SampleBucket {
+    const EMPTY: SampleBucket = SampleBucket { min_position: i32::MAX, max_position: i32::MIN, end_position: 0, zero_clicks: 0, zero_landings: 0 };
+
+    fn then(self, next: SampleBucket) -> SampleBucket {
+        SampleBucket {
+            min_position: self.min_position.min(next.min_position),
+            max_position: self.max_position.max(next.max_position),
+            end_position: next.end_position,
+            zero_clicks: self.zero_clicks + next.zero_clicks,
+            zero_landings: self.zero_landings + next.zero_landings,
+        }
+    }
+}
+
+/// Where a trajectory went, downsampled to at most `max_buckets` buckets of steps, each keeping
+/// the range of positions it ended steps at and the zeros it counted.
+#[derive(Debug, Clone)]
+pub struct TrajectorySamples {
+    size: i32,
+    start_position: Option<i32>,
+    buckets: Buckets<SampleBucket>,
+}
+
+impl TrajectorySamples {
+    pub fn new(size: i32, max_buckets: usize) -> TrajectorySamples {
+        TrajectorySamples { size, start_position: None, buckets: Buckets::new(max_buckets) }
+    }
+
+    pub fn from_steps<I: IntoIterator<Item = DialStep>>(size: i32, steps: I, max_buckets: usize) -> TrajectorySamples {
+        let mut samples = TrajectorySamples::new(size, max_buckets);
+        steps.into_iter().for_each(|step| samples.push(&step));
+        samples
+    }
+
+    pub fn push(&mut self, step: &DialStep) {
+        self.start_position.get_or_insert(step.position_before);
+        let sample = SampleBucket {
+            min_position: step.position_after,
+            max_position: step.position_after,
+            end_position: step.position_after,
+            zero_clicks: step.zero_clicks,
+            zero_landings: step.landed_on_zero as u64,
+        };
+
+        let bucket = self.buckets.next(|| SampleBucket::EMPTY, SampleBucket::then);
+        *bucket = bucket.then(sample);
+    }
+
+    pub fn get_size(&self) -> i32 {
+        self.size
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.buckets.steps
+    }
+
+    /// How many steps each bucket covers. The last bucket may cover fewer.
+    pub fn bucket_width(&self) -> u64 {
+        self.buckets.bucket_width
+    }
+
+    pub fn buckets(&self) -> &[SampleBucket] {
+        &self.buckets.buckets
+    }
+}
+
+/// How many clicks arrived at each position, for each bucket of steps of a trajectory.
+#[derive(Debug, Clone)]
+pub struct VisitHeatmap {
+    size: i32,
+    buckets: Buckets<VisitDifferences>,
+}
+
+impl VisitHeatmap {
+    pub fn new(size: i32, max_columns: usize) -> VisitHeatmap {
+        VisitHeatmap { size, buckets: Buckets::new(max_columns) }
+    }
+
+    pub fn from_steps<I: IntoIterator<Item = DialStep>>(size: i32, steps: I, max_columns: usize) -> VisitHeatmap {
+        let mut heatmap = VisitHeatmap::new(size, max_columns);
+        steps.into_iter().for_each(|step| heatmap.push(&step));
+        heatmap
+    }
+
+    pub fn push(&mut self, step: &DialStep) {
+        let size = self.size;
+        let column = self.buckets.next(|| VisitDifferences::new(size), VisitDifferences::then);
+        column.add(&DialMovement::new(size, step.position_before, step.rotation));
+    }
+
+    pub fn get_size(&self) -> i32 {
+        self.size
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.buckets.steps
+    }
+
+    pub fn bucket_width(&self) -> u64 {
+        self.buckets.bucket_width
+    }
+
+    /// Visits to each position, one `Vec` per column of steps.
+    pub fn columns(&self) -> Vec<Vec<u64>> {
+        self.buckets.buckets.iter().map(VisitDifferences::counts).collect()
+    }
+}
+
+/// Plots position against step index. Where buckets hold several steps the range of positions
+/// they reached is shaded behind the line through their end positions. Buckets that clicked
+/// through 0 get a hollow marker on the 0 line, and a filled one if a rotation landed on 0.
+pub fn write_trajectory_svg<W: Write>(samples: &TrajectorySamples, mut output: W) -> io::Result<()> {
+    let plot_width = SVG_WIDTH - 2.0 * SVG_MARGIN;
+    let plot_height = SVG_HEIGHT - 2.0 * SVG_MARGIN;
+    let steps = samples.steps().max(1) as f64;
+    let top = (samples.get_size() - 1).max(1) as f64;
+    let x = |step: u64| SVG_MARGIN + step as f64 / steps * plot_width;
+    let y = |position: i32| SVG_MARGIN + plot_height - position as f64 / top * plot_height;
+    let width = samples.bucket_width();
+    let bucket_end = |index: usize| ((index as u64 + 1) * width).min(samples.steps());
+
+    writeln!(output, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#, SVG_WIDTH, SVG_HEIGHT, SVG_WIDTH, SVG_HEIGHT)?;
+    writeln!(output, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+    let (clicks, landings) = samples.buckets().iter().fold((0, 0), |(c, l), b| (c + b.zero_clicks, l + b.zero_landings));
+    writeln!(
+        output,
+        r#"<text x="{}" y="24" font-family="monospace" font-size="14">{} steps, {} landings on 0, {} clicks through 0 (1 point per {} steps)</text>"#,
+        SVG_MARGIN,
+        samples.steps(),
+        landings,
+        clicks,
+        width
+    )?;
+    writeln!(
+        output,
+        r#"<path d="M{x0:.1} {y0:.1} V{y1:.1} H{x1:.1}" fill="none" stroke="black"/>"#,
+        x0 = SVG_MARGIN,
+        y0 = SVG_MARGIN,
+        y1 = SVG_MARGIN + plot_height,
+        x1 = SVG_MARGIN + plot_width
+    )?;
+    writeln!(output, r#"<text x="{:.1}" y="{:.1}" font-family="monospace" font-size="12" text-anchor="end">{}</text>"#, SVG_MARGIN - 4.0, y(top as i32) + 4.0, top)?;
+    writeln!(output, r#"<text x="{:.1}" y="{:.1}" font-family="monospace" font-size="12" text-anchor="end">0</text>"#, SVG_MARGIN - 4.0, y(0) + 4.0)?;
+
+    if width > 1 {
+        let upper = samples.buckets().iter().enumerate().map(|(i, b)| format!("{:.1},{:.1}", x(bucket_end(i)), y(b.max_position)));
+        let lower = samples.buckets().iter().enumerate().rev().map(|(i, b)| format!("{:.1},{:.1}", x(bucket_end(i)), y(b.min_position)));
+        writeln!(output, r#"<polygon points="{}" fill="lightsteelblue" stroke="none"/>"#, upper.chain(lower).collect::<Vec<_>>().join(" "))?;
+    }
+
+    let start = samples.start_position.map(|position| format!("{:.1},{:.1}", x(0), y(position)));
+    let points = start
+        .into_iter()
+        .chain(samples.buckets().iter().enumerate().map(|(i, b)| format!("{:.1},{:.1}", x(bucket_end(i)), y(b.end_position))));
+    writeln!(output, r#"<polyline points="{}" fill="none" stroke="steelblue" stroke-width="1"/>"#, points.collect::<Vec<_>>().join(" "))?;
+
+    for (index, bucket) in samples.buckets().iter().enumerate().filter(|(_, b)| b.zero_clicks > 0) {
+        writeln!(
+            output,
+            r#"<circle class="zero" cx="{:.1}" cy="{:.1}" r="3" stroke="crimson" fill="{}"><title>{} clicks through 0, {} landings</title></circle>"#,
+            x(bucket_end(index)),
+            y(0),
+            if bucket.zero_landings > 0 { "crimson" } else { "none" },
+            bucket.zero_clicks,
+            bucket.zero_landings
+        )?;
+    }
+
+    writeln!(output, "</svg>")
+}
+
+/// Writes a binary PPM with one column per bucket of steps and one row per position, 0 at the
+/// top. Colours run from black through red and yellow to white on a log scale of visits.
+pub fn write_heatmap_ppm<W: Write>(heatmap: &VisitHeatmap, mut output: W) -> io::Result<()> {
+    let columns = heatmap.columns();
+    let max_visits = columns.iter().flatten().copied().max().unwrap_or(0);
+    let scale = ((max_visits + 1) as f64).ln();
+
+    write!(output, "P6\n{} {}\n255\n", columns.len(), heatmap.get_size())?;
+    let mut pixels = Vec::with_capacity(columns.len() * heatmap.get_size() as usize * 3);
+    for position in 0..heatmap.get_size() as usize {
+        for column in &columns {
+            let heat = if scale > 0.0 { ((column[position] + 1) as f64).ln() / scale } else { 0.0 };
+            let channel = |offset: f64| ((3.0 * heat - offset).clamp(0.0, 1.0) * 255.0).round() as u8;
+            pixels.extend_from_slice(&[channel(0.0), channel(1.0), channel(2.0)]);
+        }
+    }
+    output.write_all(&pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seeded_rotations, test_rotations, trajectory, visit_histogram, Dial};
+
+    #[test]
+    fn samples_keep_every_step_when_there_is_room() {
+        let samples = TrajectorySamples::from_steps(100, trajectory(Dial::new(50, 0).unwrap(), test_rotations()), 16);
+
+        assert_eq!(samples.bucket_width(), 1);
+        assert_eq!(samples.buckets().iter().map(|b| b.end_position).collect::<Vec<_>>(), vec![82, 52, 0, 95, 55, 0, 99, 0, 14, 32]);
+        assert_eq!(samples.buckets().iter().map(|b| b.zero_landings).sum::<u64>(), 3);
+        assert_eq!(samples.buckets().iter().map(|b| b.zero_clicks).sum::<u64>(), 6);
+    }
+
+    #[test]
+    fn long_trajectories_are_downsampled_without_losing_counts() {
+        let rotations = seeded_rotations(22, 100_000);
+        let last = trajectory(Dial::new(50, 0).unwrap(), &rotations).last().unwrap();
+
+        let samples = TrajectorySamples::from_steps(100, trajectory(Dial::new(50, 0).unwrap(), &rotations), 500);
+
+        assert!(samples.buckets().len() <= 500);
+        assert_eq!(samples.bucket_width(), 256);
+        assert_eq!(samples.buckets().last().unwrap().end_position, last.position_after);
+        assert_eq!(samples.buckets().iter().map(|b| b.zero_clicks).sum::<u64>(), last.total_zero_clicks);
+        assert_eq!(samples.buckets().iter().map(|b| b.zero_landings).sum::<u64>(), last.total_zero_landings);
+    }
+
+    #[test]
+    fn downsampled_buckets_match_direct_bucketing() {
+        let rotations = seeded_rotations(22, 1000);
+        let steps = trajectory(Dial::new(50, 0).unwrap(), &rotations).collect::<Vec<_>>();
+
+        let samples = TrajectorySamples::from_steps(100, steps.iter().copied(), 7);
+
+        assert_eq!(samples.bucket_width(), 256);
+        for (bucket, chunk) in samples.buckets().iter().zip(steps.chunks(256)) {
+            assert_eq!(bucket.min_position, chunk.iter().map(|s| s.position_after).min().unwrap());
+            assert_eq!(bucket.max_position, chunk.iter().map(|s| s.position_after).max().unwrap());
+            assert_eq!(bucket.zero_clicks, chunk.iter().map(|s| s.zero_clicks).sum::<u64>());
+        }
+    }
+
+    #[test]
+    fn heatmap_columns_add_up_to_the_visit_histogram() {
+        let rotations = seeded_rotations(22, 5000);
+        let dial = Dial::new(50, 0).unwrap();
+
+        let heatmap = VisitHeatmap::from_steps(100, trajectory(dial.clone(), &rotations), 64);
+        let columns = heatmap.columns();
+
+        assert!(columns.len() <= 64);
+        let totals = (0..100).map(|position| columns.iter().map(|column| column[position]).sum::<u64>()).collect::<Vec<_>>();
+        assert_eq!(totals, visit_histogram(&dial, &rotations).counts());
+    }
+
+    #[test]
+    fn svg_marks_each_bucket_that_clicked_through_zero() {
+        let samples = TrajectorySamples::from_steps(100, trajectory(Dial::new(50, 0).unwrap(), test_rotations()), 16);
+        let mut output = Vec::new();
+
+        write_trajectory_svg(&samples, &mut output).unwrap();
+
+        let svg = String::from_utf8(output).unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("10 steps, 3 landings on 0, 6 clicks through 0"));
+        // L68, R48, R60, L55, L99 and L82 each click through 0 once.
+        assert_eq!(svg.matches(r#"class="zero""#).count(), 6);
+        assert_eq!(svg.matches(r#"fill="crimson""#).count(), 3);
+        assert!(!svg.contains("<polygon"));
+    }
+
+    #[test]
+    fn ppm_has_one_pixel_per_column_and_position() {
+        let heatmap = VisitHeatmap::from_steps(12, trajectory(Dial::with_size(12, 0, 0).unwrap(), test_rotations()), 4);
+        let mut output = Vec::new();
+
+        write_heatmap_ppm(&heatmap, &mut output).unwrap();
+
+        let header = b"P6\n3 12\n255\n";
+        assert_eq!(heatmap.bucket_width(), 4);
+        assert_eq!(&output[..header.len()], header);
+        assert_eq!(output.len(), header.len() + 3 * 12 * 3);
+    }
+}
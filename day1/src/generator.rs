@@ -0,0 +1,439 @@
+use crate::{format_rotation_program, DialRotation, Direction, ReferenceDial, DEFAULT_DIAL_SIZE};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// SplitMix64. Small, fast and fully determined by its seed, which is all a fixture needs.
+#[derive(Debug, Clone)]
+pub struct GeneratorRng {
+    state: u64,
+}
+
+impl GeneratorRng {
+    pub fn new(seed: u64) -> GeneratorRng {
+        GeneratorRng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`. The modulo bias is negligible for the small bounds used here.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// A number in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AmountDistribution {
+    Uniform { min: i32, max: i32 },
+    /// Amounts spread evenly over orders of magnitude from 0 to `max`, so small amounts are common.
+    LogUniform { max: i32 },
+}
+
+impl AmountDistribution {
+    fn max(&self) -> i32 {
+        match *self {
+            AmountDistribution::Uniform { max, .. } | AmountDistribution::LogUniform { max } => max,
+        }
+    }
+
+    fn sample(&self, rng: &mut GeneratorRng) -> i32 {
+        match *self {
+            AmountDistribution::Uniform { min, max } => min + rng.below((max - min) as u64 + 1) as i32,
+            AmountDistribution::LogUniform { max } => {
+                let amount = (rng.next_f64() * (max as f64 + 1.0).ln()).exp() - 1.0;
+                (amount as i32).min(max)
+            },
+        }
+    }
+}
+
+/// Per-rotation probabilities of the cases the puzzle rules are easiest to get wrong on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeCaseDensity {
+    /// The rotation finishes exactly on 0, if 0 is within the maximum amount either way round.
+    pub zero_landings: f64,
+    /// The amount is a whole number of turns.
+    pub whole_turns: f64,
+    /// The rotation lands on 0 and the next one starts from there.
+    pub zero_starts: f64,
+}
+
+impl Default for EdgeCaseDensity {
+    fn default() -> EdgeCaseDensity {
+        EdgeCaseDensity { zero_landings: 0.05, whole_turns: 0.02, zero_starts: 0.05 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorConfig {
+    pub seed: u64,
+    pub length: usize,
+    pub size: i32,
+    pub start_position: i32,
+    pub amounts: AmountDistribution,
+    pub edge_cases: EdgeCaseDensity,
+}
+
+impl Default for GeneratorConfig {
+    /// Roughly the shape of a real puzzle input.
+    fn default() -> GeneratorConfig {
+        GeneratorConfig {
+            seed: 1,
+            length: 4424,
+            size: DEFAULT_DIAL_SIZE,
+            start_position: 50,
+            amounts: AmountDistribution::Uniform { min: 1, max: 999 },
+            edge_cases: EdgeCaseDensity::default(),
+        }
+    }
+}
+
+impl GeneratorConfig {
+    pub const USAGE: &str = "[--seed <n>] [--length <n>] [--size <n>] [--start <position>] [--max-amount <n>] [--log-amounts] \
+                             [--zero-landings <p>] [--whole-turns <p>] [--zero-starts <p>]";
+
+    /// Reads a config from command line arguments, returning it with any arguments that are not options.
+    pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<(GeneratorConfig, Vec<String>), String> {
+        fn value<T: FromStr>(option: &str, value: Option<String>) -> Result<T, String> {
+            let value = value.ok_or_else(|| format!("{} needs a value", option))?;
+            value.parse().map_err(|_| format!("Invalid value {:?} for {}", value, option))
+        }
+
+        let mut config = GeneratorConfig::default();
+        let mut max_amount = config.amounts.max();
+        let mut log_amounts = false;
+        let mut rest = Vec::new();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => config.seed = value(&arg, args.next())?,
+                "--length" => config.length = value(&arg, args.next())?,
+                "--size" => config.size = value(&arg, args.next())?,
+                "--start" => config.start_position = value(&arg, args.next())?,
+                "--max-amount" => max_amount = value(&arg, args.next())?,
+                "--log-amounts" => log_amounts = true,
+                "--zero-landings" => config.edge_cases.zero_landings = value(&arg, args.next())?,
+                "--whole-turns" => config.edge_cases.whole_turns = value(&arg, args.next())?,
+                "--zero-starts" => config.edge_cases.zero_starts = value(&arg, args.next())?,
+                _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+                _ => rest.push(arg),
+            }
+        }
+
+        config.amounts = if log_amounts {
+            AmountDistribution::LogUniform { max: max_amount }
+        } else {
+            AmountDistribution::Uniform { min: 1.min(max_amount), max: max_amount }
+        };
+        config.validate()?;
+        Ok((config, rest))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.size < 1 {
+            return Err("Invalid dial size".to_string());
+        }
+        if !(0..self.size).contains(&self.start_position) {
+            return Err("Invalid dial starting position".to_string());
+        }
+        match self.amounts {
+            AmountDistribution::Uniform { min, max } if min < 0 || min > max => {
+                return Err(format!("Invalid amount range {}..={}", min, max));
+            },
+            AmountDistribution::LogUniform { max } if max < 0 => return Err(format!("Invalid maximum amount {}", max)),
+            _ => {},
+        }
+
+        let EdgeCaseDensity { zero_landings, whole_turns, zero_starts } = self.edge_cases;
+        for (name, probability) in [("zero landings", zero_landings), ("whole turns", whole_turns), ("zero starts", zero_starts)] {
+            if !(0.0..=1.0).contains(&probability) {
+                return Err(format!("Density of {} must be between 0 and 1, got {}", name, probability));
+            }
+        }
+        if zero_landings + whole_turns + zero_starts > 1.0 {
+            return Err("Edge case densities add up to more than 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub fn generate_rotations(config: &GeneratorConfig) -> Result<Vec<DialRotation>, String> {
+    config.validate()?;
+
+    let mut rng = GeneratorRng::new(config.seed);
+    let mut rotations = Vec::with_capacity(config.length);
+    let mut position = config.start_position as i64;
+    let max_turns = (config.amounts.max() / config.size) as u64;
+
+    while rotations.len() < config.length {
+        let direction = if rng.chance(0.5) { Direction::Left } else { Direction::Right };
+        let roll = rng.next_f64();
+        let edge_cases = config.edge_cases;
+        let landing_odds = edge_cases.zero_landings + edge_cases.zero_starts;
+        let max_amount = config.amounts.max() as i64;
+        let size = config.size as i64;
+
+        // A zero landing turns the other way if it is too far to reach under the maximum amount,
+        // and is dropped for an ordinary amount if it is too far either way.
+        let clicks_to_zero = |direction: Direction| match direction {
+            Direction::Right => (size - position) % size,
+            Direction::Left => position,
+        };
+        let landing = if roll < landing_odds {
+            [direction, direction.opposite()]
+                .into_iter()
+                .map(|direction| (direction, clicks_to_zero(direction)))
+                .find(|&(_, to_zero)| to_zero <= max_amount)
+        } else {
+            None
+        };
+
+        let rotation = match landing {
+            Some((direction, to_zero)) => {
+                let turns = rng.below(((max_amount - to_zero) / size) as u64 + 1);
+                DialRotation::new(direction, (to_zero + turns as i64 * size) as i32)
+            },
+            None if (landing_odds..landing_odds + edge_cases.whole_turns).contains(&roll) => {
+                DialRotation::new(direction, rng.below(max_turns + 1) as i32 * config.size)
+            },
+            None => DialRotation::new(direction, config.amounts.sample(&mut rng)),
+        };
+        position = (position + rotation.get_signed_value()).rem_euclid(size);
+        rotations.push(rotation);
+
+        // The rotation after a zero start leaves 0, however it was picked.
+        if landing.is_some() && roll >= edge_cases.zero_landings && rotations.len() < config.length {
+            let direction = if rng.chance(0.5) { Direction::Left } else { Direction::Right };
+            let rotation = DialRotation::new(direction, config.amounts.sample(&mut rng));
+            position = rotation.get_signed_value().rem_euclid(size);
+            rotations.push(rotation);
+        }
+    }
+    Ok(rotations)
+}
+
+/// The answers for a generated file, as stored in its sidecar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpectedAnswers {
+    pub seed: u64,
+    pub size: i32,
+    pub start_position: i32,
+    pub rotations: usize,
+    pub part1: u64,
+    pub part2: u64,
+}
+
+impl ExpectedAnswers {
+    /// Works the answers out with `ReferenceDial`, one click at a time.
+    pub fn simulate(config: &GeneratorConfig, rotations: &[DialRotation]) -> Result<ExpectedAnswers, String> {
+        let mut dial = ReferenceDial::new(config.size, config.start_position)?;
+        rotations.iter().for_each(|rotation| dial.turn(rotation));
+
+        Ok(ExpectedAnswers {
+            seed: config.seed,
+            size: config.size,
+            start_position: config.start_position,
+            rotations: rotations.len(),
+            part1: dial.zero_landings(),
+            part2: dial.zero_clicks(),
+        })
+    }
+}
+
+impl fmt::Display for ExpectedAnswers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "seed={}", self.seed)?;
+        writeln!(f, "size={}", self.size)?;
+        writeln!(f, "start={}", self.start_position)?;
+        writeln!(f, "rotations={}", self.rotations)?;
+        writeln!(f, "part1={}", self.part1)?;
+        writeln!(f, "part2={}", self.part2)
+    }
+}
+
+impl FromStr for ExpectedAnswers {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let field = |name: &str| {
+            s.lines()
+                .find_map(|line| line.trim().strip_prefix(name)?.strip_prefix('='))
+                .ok_or_else(|| format!("Missing {} in answers", name))
+        };
+        fn number<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+            value.parse().map_err(|_| format!("Invalid {} {:?} in answers", name, value))
+        }
+
+        Ok(ExpectedAnswers {
+            seed: number("seed", field("seed")?)?,
+            size: number("size", field("size")?)?,
+            start_position: number("start", field("start")?)?,
+            rotations: number("rotations", field("rotations")?)?,
+            part1: number("part1", field("part1")?)?,
+            part2: number("part2", field("part2")?)?,
+        })
+    }
+}
+
+/// Where the answers for a generated rotation file are written: the same path with `.answers` added.
+pub fn answers_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut answers = path.as_ref().as_os_str().to_owned();
+    answers.push(".answers");
+    PathBuf::from(answers)
+}
+
+/// Generates a rotation file at `path` and its answers next to it.
+pub fn write_generated_input<P: AsRef<Path>>(config: &GeneratorConfig, path: P) -> Result<ExpectedAnswers, String> {
+    let path = path.as_ref();
+    let rotations = generate_rotations(config)?;
+    let answers = ExpectedAnswers::simulate(config, &rotations)?;
+
+    fs::write(path, format_rotation_program(&rotations)).map_err(|error| format!("{}: {}", path.display(), error))?;
+    let sidecar = answers_path(path);
+    fs::write(&sidecar, answers.to_string()).map_err(|error| format!("{}: {}", sidecar.display(), error))?;
+    Ok(answers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dial;
+
+    fn config(seed: u64, edge_cases: EdgeCaseDensity) -> GeneratorConfig {
+        GeneratorConfig { seed, length: 2000, edge_cases, ..GeneratorConfig::default() }
+    }
+
+    fn no_edge_cases() -> EdgeCaseDensity {
+        EdgeCaseDensity { zero_landings: 0.0, whole_turns: 0.0, zero_starts: 0.0 }
+    }
+
+    fn landing_positions(config: &GeneratorConfig, rotations: &[DialRotation]) -> Vec<i32> {
+        let mut dial = Dial::with_size(config.size, config.start_position, 0).unwrap();
+        rotations.iter().map(|rotation| dial.rotate(rotation).end).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_rotations() {
+        let config = config(7, EdgeCaseDensity::default());
+
+        assert_eq!(generate_rotations(&config).unwrap(), generate_rotations(&config).unwrap());
+        assert_ne!(generate_rotations(&config).unwrap(), generate_rotations(&GeneratorConfig { seed: 8, ..config }).unwrap());
+    }
+
+    #[test]
+    fn rotations_follow_the_amount_distribution() {
+        let uniform = GeneratorConfig { amounts: AmountDistribution::Uniform { min: 5, max: 10 }, ..config(1, no_edge_cases()) };
+        let rotations = generate_rotations(&uniform).unwrap();
+        assert_eq!(rotations.len(), 2000);
+        assert!(rotations.iter().all(|r| (5..=10).contains(&r.get_value())));
+
+        let log = GeneratorConfig { amounts: AmountDistribution::LogUniform { max: 100_000 }, ..config(1, no_edge_cases()) };
+        let rotations = generate_rotations(&log).unwrap();
+        assert!(rotations.iter().all(|r| (0..=100_000).contains(&r.get_value())));
+        assert!(rotations.iter().filter(|r| r.get_value() < 100).count() > 500);
+    }
+
+    #[test]
+    fn full_zero_landing_density_lands_every_rotation_on_zero() {
+        let config = config(3, EdgeCaseDensity { zero_landings: 1.0, ..no_edge_cases() });
+        let rotations = generate_rotations(&config).unwrap();
+
+        assert!(landing_positions(&config, &rotations).iter().all(|&position| position == 0));
+        assert!(rotations.iter().all(|r| r.get_value() <= 999));
+    }
+
+    #[test]
+    fn zero_landings_never_exceed_the_maximum_amount() {
+        let config = GeneratorConfig {
+            amounts: AmountDistribution::Uniform { min: 1, max: 10 },
+            ..config(5, EdgeCaseDensity { zero_landings: 0.5, zero_starts: 0.5, ..no_edge_cases() })
+        };
+        let rotations = generate_rotations(&config).unwrap();
+
+        assert!(rotations.iter().all(|r| r.get_value() <= 10));
+        let landings = landing_positions(&config, &rotations).iter().filter(|&&position| position == 0).count();
+        assert!(landings > 0 && landings < rotations.len(), "{} landings on 0", landings);
+    }
+
+    #[test]
+    fn full_whole_turn_density_only_turns_whole_turns() {
+        let config = config(4, EdgeCaseDensity { whole_turns: 1.0, ..no_edge_cases() });
+        let rotations = generate_rotations(&config).unwrap();
+
+        assert!(rotations.iter().all(|r| r.get_value() % 100 == 0));
+        assert!(rotations.iter().any(|r| r.get_value() >= 500));
+    }
+
+    #[test]
+    fn zero_starts_are_followed_by_a_rotation_from_zero() {
+        let config = config(5, EdgeCaseDensity { zero_starts: 0.5, ..no_edge_cases() });
+        let rotations = generate_rotations(&config).unwrap();
+        let landings = landing_positions(&config, &rotations);
+
+        let zero_landings = landings.iter().filter(|&&position| position == 0).count();
+        assert!(zero_landings > 400, "{} landings on 0", zero_landings);
+        assert_eq!(rotations.len(), 2000);
+    }
+
+    #[test]
+    fn expected_answers_agree_with_the_dial() {
+        let config = config(6, EdgeCaseDensity { zero_landings: 0.2, whole_turns: 0.2, zero_starts: 0.2 });
+        let rotations = generate_rotations(&config).unwrap();
+        let mut part1 = Dial::new(50, 0).unwrap();
+        let mut part2 = Dial::new(50, 0).unwrap();
+        for rotation in &rotations {
+            part1.turn_dial_in_direction_part1(rotation).unwrap();
+            part2.turn_dial_in_direction_part2(rotation).unwrap();
+        }
+
+        let answers = ExpectedAnswers::simulate(&config, &rotations).unwrap();
+
+        assert_eq!((answers.part1, answers.part2), (part1.get_zero_count() as u64, part2.get_zero_count() as u64));
+    }
+
+    #[test]
+    fn answers_round_trip_through_text() {
+        let answers = ExpectedAnswers { seed: 9, size: 100, start_position: 50, rotations: 10, part1: 3, part2: 6 };
+
+        assert_eq!(answers.to_string().parse::<ExpectedAnswers>(), Ok(answers));
+        assert!("seed=9\npart1=3\n".parse::<ExpectedAnswers>().is_err());
+    }
+
+    #[test]
+    fn invalid_configs_are_rejected() {
+        let densities = EdgeCaseDensity { zero_landings: 0.6, whole_turns: 0.6, zero_starts: 0.0 };
+        assert!(generate_rotations(&config(1, densities)).is_err());
+        assert!(generate_rotations(&GeneratorConfig { start_position: 100, ..GeneratorConfig::default() }).is_err());
+        assert!(generate_rotations(&GeneratorConfig { amounts: AmountDistribution::Uniform { min: 9, max: 3 }, ..GeneratorConfig::default() }).is_err());
+    }
+
+    #[test]
+    fn config_parses_from_arguments() {
+        let args = ["out.txt", "--seed", "42", "--length", "10", "--max-amount", "5000", "--log-amounts", "--zero-starts", "0.3"].map(String::from);
+
+        let (config, rest) = GeneratorConfig::parse_args(args).unwrap();
+
+        assert_eq!(rest, vec!["out.txt"]);
+        assert_eq!((config.seed, config.length), (42, 10));
+        assert_eq!(config.amounts, AmountDistribution::LogUniform { max: 5000 });
+        assert_eq!(config.edge_cases.zero_starts, 0.3);
+        assert!(GeneratorConfig::parse_args(["--zero-starts", "2"].map(String::from)).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{Dial, DialMovement, DialRotation};
+use std::borrow::Borrow;
+
+/// How many clicks arrived at each position of the dial. The starting position only counts if
+/// a later click comes back to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisitHistogram {
+    counts: Vec<u64>,
+}
+
+impl VisitHistogram {
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Clicks that arrived at `position`, or `None` if the dial has no such position.
+    pub fn visits(&self, position: i32) -> Option<u64> {
+        usize::try_from(position).ok().and_then(|position| self.counts.get(position)).copied()
+    }
+
+    pub fn total_clicks(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The most visited position and its count, preferring the lowest position on ties.
+    pub fn most_visited(&self) -> (i32, u64) {
+        let (position, &count) = self.counts.iter().enumerate().rev().max_by_key(|(_, count)| **count).unwrap();
+        (position as i32, count)
+    }
+
+    /// The least visited position and its count, preferring the lowest position on ties.
+    pub fn least_visited(&self) -> (i32, u64) {
+        let (position, &count) = self.counts.iter().enumerate().min_by_key(|(_, count)| **count).unwrap();
+        (position as i32, count)
+    }
+}
+
+/// Builds the histogram without walking individual clicks, from `VisitDifferences`.
+pub fn visit_histogram<R>(dial: &Dial, rotations: R) -> VisitHistogram
+where
+    R: IntoIterator,
+    R::Item: Borrow<DialRotation>,
+{
+    let mut visits = VisitDifferences::new(dial.get_size());
+    let mut dial = dial.clone();
+
+    for rotation in rotations {
+        visits.add(&dial.rotate(rotation.borrow()));
+    }
+
+    VisitHistogram { counts: visits.counts() }
+}
+
+/// Visits to each position of a dial of `size` positions, gathered one movement at a time: full
+/// turns add to every position and the partial arc adds to one or two contiguous ranges of a
+/// difference array.
+#[derive(Debug, Clone)]
+pub(crate) struct VisitDifferences {
+    full_turns: u64,
+    differences: Vec<i64>,
+}
+
+impl VisitDifferences {
+    pub(crate) fn new(size: i32) -> VisitDifferences {
+        VisitDifferences { full_turns: 0, differences: vec![0; size as usize + 1] }
+    }
+
+    pub(crate) fn add(&mut self, movement: &DialMovement) {
+        self.full_turns += movement.full_turns();
+
+        if let Some((first_position, clicks)) = movement.partial_arc() {
+            let size = self.differences.len() - 1;
+            let end = first_position as usize + clicks as usize;
+            self.differences[first_position as usize] += 1;
+            if end <= size {
+                self.differences[end] -= 1;
+            } else {
+                self.differences[size] -= 1;
+                self.differences[0] += 1;
+                self.differences[end - size] -= 1;
+            }
+        }
+    }
+
+    /// These visits followed by `next`'s, on a dial of the same size.
+    pub(crate) fn then(mut self, next: VisitDifferences) -> VisitDifferences {
+        self.full_turns += next.full_turns;
+        self.differences.iter_mut().zip(next.differences).for_each(|(mine, theirs)| *mine += theirs);
+        self
+    }
+
+    pub(crate) fn counts(&self) -> Vec<u64> {
+        let mut running = 0i64;
+        self.differences[..self.differences.len() - 1]
+            .iter()
+            .map(|difference| {
+                running += difference;
+                self.full_turns + running as u64
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_dial_rotation, test_rotations, DialMovement, Direction};
+
+    #[test]
+    fn histogram_matches_click_by_click_counts() {
+        let rotations = ["R250", "L37", "L199", "R3", "L100", "R0", "L71", "R99"].map(get_dial_rotation);
+        let dial = Dial::with_size(40, 7, 0).unwrap();
+
+        let histogram = visit_histogram(&dial, rotations);
+
+        let mut expected = vec![0u64; 40];
+        let mut position = 7i64;
+        for rotation in rotations {
+            let step = rotation.get_signed_value().signum();
+            for _ in 0..rotation.get_value() {
+                position = (position + step).rem_euclid(40);
+                expected[position as usize] += 1;
+            }
+        }
+        assert_eq!(histogram.counts(), expected.as_slice());
+    }
+
+    #[test]
+    fn histogram_total_is_the_number_of_clicks() {
+        let histogram = visit_histogram(&Dial::new(50, 0).unwrap(), test_rotations());
+
+        assert_eq!(histogram.total_clicks(), 462);
+        assert_eq!(histogram.visits(0), Some(6));
+    }
+
+    #[test]
+    fn visits_outside_the_dial_are_none() {
+        let histogram = visit_histogram(&Dial::with_size(10, 0, 0).unwrap(), ["R13"].map(get_dial_rotation));
+
+        assert_eq!(histogram.visits(9), Some(1));
+        assert_eq!(histogram.visits(10), None);
+        assert_eq!(histogram.visits(-1), None);
+    }
+
+    #[test]
+    fn negative_amounts_visit_the_same_positions_as_the_opposite_direction() {
+        let dial = Dial::new(50, 0).unwrap();
+        let histogram = visit_histogram(&dial, [DialRotation::new(Direction::Left, -250)]);
+
+        assert_eq!(histogram.counts(), visit_histogram(&dial, [DialRotation::new(Direction::Right, 250)]).counts());
+        assert_eq!(histogram.total_clicks(), 250);
+        assert_eq!(DialMovement::new(100, 50, DialRotation::new(Direction::Left, -250)).full_turns(), 2);
+    }
+
+    #[test]
+    fn most_and_least_visited_prefer_lowest_position_on_ties() {
+        let histogram = visit_histogram(&Dial::with_size(10, 0, 0).unwrap(), ["R13", "L2"].map(get_dial_rotation));
+
+        assert_eq!(histogram.counts(), &[1, 3, 3, 2, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(histogram.most_visited(), (1, 3));
+        assert_eq!(histogram.least_visited(), (0, 1));
+    }
+}
@@ -0,0 +1,277 @@
+use crate::{CountingRule, Dial, DialMovement, DialRotation};
+
+/// A `Dial` that remembers the rotations it has been through, so they can be undone, redone or
+/// jumped between while debugging a rotation file.
+///
+/// Undo reverses a rotation directly: the rotation and the position it ended at pin down where it
+/// started, and from there the rule gives back exactly the zeros it counted. Jumps restore the
+/// nearest dial saved every `checkpoint_interval` steps and re-run the rotations after it, so only
+/// one dial per interval is kept alongside the rotations themselves.
+#[derive(Debug, Clone)]
+pub struct UndoableDial {
+    dial: Dial,
+    rule: CountingRule,
+    checkpoint_interval: usize,
+    /// Dials after 0, k, 2k, ... steps of `rotations`.
+    checkpoints: Vec<Dial>,
+    /// Every rotation applied, including any that have been undone but can still be redone.
+    rotations: Vec<DialRotation>,
+    step: usize,
+}
+
+impl UndoableDial {
+    pub fn new(dial: Dial, rule: CountingRule, checkpoint_interval: usize) -> Result<UndoableDial, String> {
+        if checkpoint_interval == 0 {
+            return Err("Checkpoint interval must be at least 1".to_string());
+        }
+
+        Ok(UndoableDial {
+            checkpoints: vec![dial.clone()],
+            dial,
+            rule,
+            checkpoint_interval,
+            rotations: Vec::new(),
+            step: 0,
+        })
+    }
+
+    pub fn dial(&self) -> &Dial {
+        &self.dial
+    }
+
+    pub fn rule(&self) -> CountingRule {
+        self.rule
+    }
+
+    /// How many rotations the dial is currently through.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Every rotation applied so far, including undone ones that can be redone.
+    pub fn history(&self) -> &[DialRotation] {
+        &self.rotations
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.step > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.step < self.rotations.len()
+    }
+
+    /// Turns the dial, discarding anything that could have been redone. If the zero count would
+    /// overflow, the dial and its history are left as they were.
+    pub fn apply(&mut self, rotation: &DialRotation) -> Result<DialMovement, String> {
+        let mut turned = self.dial.clone();
+        match self.rule {
+            CountingRule::LandingsOnZero => turned.turn_dial_in_direction_part1(rotation)?,
+            CountingRule::ClicksThroughZero => turned.turn_dial_in_direction_part2(rotation)?,
+        }
+
+        self.rotations.truncate(self.step);
+        self.checkpoints.truncate(self.step / self.checkpoint_interval + 1);
+        self.rotations.push(*rotation);
+        self.advance()
+    }
+
+    /// Takes back the last rotation, returning it, or `None` if there is nothing to undo. Fails,
+    /// leaving the dial as it was, if the zeros the rotation counted cannot be taken back off the
+    /// zero count.
+    pub fn undo(&mut self) -> Result<Option<DialRotation>, String> {
+        if !self.can_undo() {
+            return Ok(None);
+        }
+
+        let rotation = self.rotations[self.step - 1];
+        let size = self.dial.get_size();
+        let start = (self.dial.get_current_position() as i64 - rotation.get_signed_value()).rem_euclid(size as i64) as i32;
+        let counted = self.rule.count_movement(&DialMovement::new(size, start, rotation));
+        let zero_count = i32::try_from(counted)
+            .ok()
+            .and_then(|counted| self.dial.get_zero_count().checked_sub(counted))
+            .ok_or_else(|| format!("Cannot undo {}: it counted {} zeros but the count is {}", rotation, counted, self.dial.get_zero_count()))?;
+
+        self.dial = Dial::with_size(size, start, zero_count)?;
+        self.step -= 1;
+        Ok(Some(rotation))
+    }
+
+    /// Runs the next undone rotation again, or returns `None` if there is nothing to redo.
+    pub fn redo(&mut self) -> Result<Option<DialMovement>, String> {
+        if !self.can_redo() {
+            return Ok(None);
+        }
+
+        self.advance().map(Some)
+    }
+
+    /// Moves to the dial as it was after `step` rotations, forwards or backwards.
+    pub fn jump_to(&mut self, step: usize) -> Result<(), String> {
+        if step > self.rotations.len() {
+            return Err(format!("Cannot jump to step {}, only {} rotations are recorded", step, self.rotations.len()));
+        }
+
+        if step < self.step || step - self.step > self.checkpoint_interval {
+            let checkpoint = step / self.checkpoint_interval;
+            self.dial = self.checkpoints[checkpoint].clone();
+            self.step = checkpoint * self.checkpoint_interval;
+        }
+        while self.step < step {
+            self.advance()?;
+        }
+        Ok(())
+    }
+
+    /// Runs the next recorded rotation, saving a checkpoint if this step is due one. Fails,
+    /// leaving the dial at the current step, if the zero count would overflow.
+    fn advance(&mut self) -> Result<DialMovement, String> {
+        let rotation = self.rotations[self.step];
+        let movement = DialMovement::new(self.dial.get_size(), self.dial.get_current_position(), rotation);
+        match self.rule {
+            CountingRule::LandingsOnZero => self.dial.turn_dial_in_direction_part1(&rotation)?,
+            CountingRule::ClicksThroughZero => self.dial.turn_dial_in_direction_part2(&rotation)?,
+        }
+
+        self.step += 1;
+        if self.step.is_multiple_of(self.checkpoint_interval) && self.checkpoints.len() == self.step / self.checkpoint_interval {
+            self.checkpoints.push(self.dial.clone());
+        }
+        Ok(movement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_dial_rotation, test_rotations};
+
+    fn states_after_each_step(rule: CountingRule) -> Vec<(i32, i32)> {
+        let mut dial = Dial::new(50, 0).unwrap();
+        let mut states = vec![(50, 0)];
+        for rotation in test_rotations() {
+            match rule {
+                CountingRule::LandingsOnZero => dial.turn_dial_in_direction_part1(&rotation).unwrap(),
+                CountingRule::ClicksThroughZero => dial.turn_dial_in_direction_part2(&rotation).unwrap(),
+            }
+            states.push((dial.get_current_position(), dial.get_zero_count()));
+        }
+        states
+    }
+
+    fn state(dial: &UndoableDial) -> (i32, i32) {
+        (dial.dial().get_current_position(), dial.dial().get_zero_count())
+    }
+
+    #[test]
+    fn undoing_every_step_retraces_the_run_for_both_rules() {
+        for rule in CountingRule::ALL {
+            let expected = states_after_each_step(rule);
+            let mut dial = UndoableDial::new(Dial::new(50, 0).unwrap(), rule, 3).unwrap();
+            for rotation in test_rotations() {
+                dial.apply(&rotation).unwrap();
+            }
+
+            for step in (0..10).rev() {
+                assert!(dial.undo().unwrap().is_some());
+                assert_eq!(state(&dial), expected[step], "{} at step {}", rule, step);
+            }
+            assert_eq!(dial.undo(), Ok(None));
+        }
+    }
+
+    #[test]
+    fn undo_reverses_clicks_from_many_full_turns() {
+        let mut dial = UndoableDial::new(Dial::new(0, 0).unwrap(), CountingRule::ClicksThroughZero, 4).unwrap();
+
+        dial.apply(&get_dial_rotation("R1000")).unwrap();
+        dial.apply(&get_dial_rotation("L250")).unwrap();
+        assert_eq!(state(&dial), (50, 12));
+
+        dial.undo().unwrap();
+        assert_eq!(state(&dial), (0, 10));
+        dial.undo().unwrap();
+        assert_eq!(state(&dial), (0, 0));
+    }
+
+    #[test]
+    fn redo_replays_undone_steps_until_a_new_rotation_is_applied() {
+        let expected = states_after_each_step(CountingRule::ClicksThroughZero);
+        let mut dial = UndoableDial::new(Dial::new(50, 0).unwrap(), CountingRule::ClicksThroughZero, 2).unwrap();
+        for rotation in test_rotations() {
+            dial.apply(&rotation).unwrap();
+        }
+
+        for _ in 0..4 {
+            dial.undo().unwrap();
+        }
+        dial.redo().unwrap();
+        dial.redo().unwrap();
+        assert_eq!(state(&dial), expected[8]);
+
+        dial.apply(&get_dial_rotation("R18")).unwrap();
+        assert!(!dial.can_redo());
+        assert_eq!(dial.history().len(), 9);
+        assert_eq!(dial.redo(), Ok(None));
+    }
+
+    #[test]
+    fn jumping_to_any_step_matches_the_run() {
+        let expected = states_after_each_step(CountingRule::LandingsOnZero);
+
+        for interval in [1, 3, 4, 20] {
+            let mut dial = UndoableDial::new(Dial::new(50, 0).unwrap(), CountingRule::LandingsOnZero, interval).unwrap();
+            for rotation in test_rotations() {
+                dial.apply(&rotation).unwrap();
+            }
+
+            for step in [7, 2, 10, 0, 9, 3, 3] {
+                dial.jump_to(step).unwrap();
+                assert_eq!(dial.step(), step);
+                assert_eq!(state(&dial), expected[step], "interval {} at step {}", interval, step);
+            }
+            assert!(dial.jump_to(11).is_err());
+        }
+    }
+
+    #[test]
+    fn applying_after_a_jump_back_discards_stale_checkpoints() {
+        let mut dial = UndoableDial::new(Dial::new(50, 0).unwrap(), CountingRule::ClicksThroughZero, 2).unwrap();
+        for rotation in test_rotations() {
+            dial.apply(&rotation).unwrap();
+        }
+
+        dial.jump_to(3).unwrap();
+        for rotation in ["R1", "R2", "R3", "R4"] {
+            dial.apply(&get_dial_rotation(rotation)).unwrap();
+        }
+        dial.jump_to(0).unwrap();
+        dial.jump_to(7).unwrap();
+
+        let mut expected = UndoableDial::new(Dial::new(50, 0).unwrap(), CountingRule::ClicksThroughZero, 2).unwrap();
+        for rotation in dial.history().to_vec() {
+            expected.apply(&rotation).unwrap();
+        }
+        assert_eq!(state(&dial), state(&expected));
+    }
+
+    #[test]
+    fn overflowing_rotation_is_refused_without_touching_the_history() {
+        let mut dial = UndoableDial::new(Dial::with_size(1, 0, i32::MAX - 1).unwrap(), CountingRule::ClicksThroughZero, 2).unwrap();
+
+        dial.apply(&get_dial_rotation("R1")).unwrap();
+        assert!(dial.apply(&get_dial_rotation("R1")).is_err());
+        assert_eq!((dial.step(), dial.history().len()), (1, 1));
+
+        assert_eq!(dial.undo(), Ok(Some(get_dial_rotation("R1"))));
+        assert_eq!(state(&dial), (0, i32::MAX - 1));
+        assert!(dial.redo().unwrap().is_some());
+        assert_eq!(state(&dial), (0, i32::MAX));
+    }
+
+    #[test]
+    fn zero_checkpoint_interval_is_an_error() {
+        assert!(UndoableDial::new(Dial::new(50, 0).unwrap(), CountingRule::LandingsOnZero, 0).is_err());
+    }
+}
@@ -0,0 +1,39 @@
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+
+/// An integer type a `Dial` can hold rotation amounts and zero counts in.
+///
+/// Every supported type fits in an `i128`, which is what the crossing arithmetic runs in, so the
+/// count is exact at every width and only converting it back into the type can fail.
+pub trait DialInt: Copy + Default + Eq + Ord + Hash + fmt::Debug + fmt::Display + FromStr + Send + Sync + 'static {
+    const NAME: &'static str;
+
+    fn to_i128(self) -> i128;
+
+    fn from_u128(value: u128) -> Option<Self>;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_dial_int {
+    ($($int:ty),*) => {$(
+        impl DialInt for $int {
+            const NAME: &'static str = stringify!($int);
+
+            fn to_i128(self) -> i128 {
+                self as i128
+            }
+
+            fn from_u128(value: u128) -> Option<Self> {
+                <$int>::try_from(value).ok()
+            }
+
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$int>::checked_add(self, other)
+            }
+        }
+    )*};
+}
+
+impl_dial_int!(i32, i64, u64, i128);
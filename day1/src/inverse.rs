@@ -0,0 +1,87 @@
+use crate::{CountingPolicy, Dial, DialRotation};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartInference {
+    /// The count each starting position produces, indexed by position.
+    pub counts_by_start: Vec<u64>,
+    /// Every starting position whose count equals the observed one, in ascending order.
+    pub matching_starts: Vec<i32>,
+}
+
+/// Works out which starting positions could have produced `observed_count` under the policy
+/// `new_policy` makes. Each starting position counts with a policy of its own.
+pub fn infer_start_positions<P, F>(
+    size: i32,
+    rotations: &[DialRotation],
+    new_policy: F,
+    observed_count: u64,
+) -> Result<StartInference, String>
+where
+    P: CountingPolicy,
+    F: Fn() -> P,
+{
+    if size < 1 {
+        return Err("Invalid dial size".to_string());
+    }
+
+    let mut counts_by_start = Vec::with_capacity(size as usize);
+    for start in 0..size {
+        let mut dial = Dial::with_size(size, start, 0)?;
+        let mut policy = new_policy();
+        for rotation in rotations {
+            dial.turn_dial_with(rotation, &mut policy);
+        }
+        counts_by_start.push(policy.count());
+    }
+
+    let matching_starts = (0..size).filter(|&start| counts_by_start[start as usize] == observed_count).collect();
+
+    Ok(StartInference { counts_by_start, matching_starts })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_dial_rotation, test_rotations, ClicksThroughZero, LandingsOnZero, TargetPasses};
+
+    #[test]
+    fn start_50_is_among_the_starts_giving_3_landings_for_test_input() {
+        let inference = infer_start_positions(100, &test_rotations(), LandingsOnZero::default, 3).unwrap();
+
+        assert_eq!(inference.counts_by_start.len(), 100);
+        assert_eq!(inference.counts_by_start[50], 3);
+        assert!(inference.matching_starts.contains(&50));
+        assert!(inference.matching_starts.iter().all(|&start| inference.counts_by_start[start as usize] == 3));
+    }
+
+    #[test]
+    fn single_rotation_pins_down_the_start() {
+        let rotations = vec![get_dial_rotation("R30")];
+
+        let inference = infer_start_positions(100, &rotations, LandingsOnZero::default, 1).unwrap();
+
+        assert_eq!(inference.matching_starts, vec![70]);
+    }
+
+    #[test]
+    fn impossible_count_has_no_matching_starts() {
+        let inference = infer_start_positions(100, &test_rotations(), ClicksThroughZero::default, 1000).unwrap();
+
+        assert!(inference.matching_starts.is_empty());
+    }
+
+    #[test]
+    fn any_counting_policy_can_be_inverted() {
+        let rotations = vec![get_dial_rotation("L5")];
+
+        let inference = infer_start_positions(12, &rotations, || TargetPasses::new([3]), 1).unwrap();
+
+        assert_eq!(inference.matching_starts, vec![4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn invalid_dial_size_is_an_error() {
+        assert!(infer_start_positions(0, &test_rotations(), LandingsOnZero::default, 0).is_err());
+        assert!(infer_start_positions(-3, &test_rotations(), || -> LandingsOnZero { unreachable!() }, 0).is_err());
+    }
+}
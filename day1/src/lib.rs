@@ -1,119 +1,462 @@
-#[derive(Debug)]
-pub struct DialRotation {
-    direction:char,
-    rotation_amount:i32,
+use std::fmt;
+use std::str::FromStr;
+
+mod animation;
+mod bank;
+mod bounded;
+mod counting;
+mod cycle;
+mod differential;
+mod export;
+mod generator;
+mod histogram;
+mod history;
+mod integer;
+mod inverse;
+mod multi;
+mod parallel;
+mod parser;
+mod reference;
+mod repair;
+mod repl;
+mod snapshot;
+mod solver;
+mod summary;
+mod targets;
+mod trajectory;
+
+pub use animation::*;
+pub use bank::*;
+pub use bounded::*;
+pub use counting::*;
+pub use cycle::*;
+pub use differential::*;
+pub use export::*;
+pub use generator::*;
+pub use histogram::*;
+pub use history::*;
+pub use integer::*;
+pub use inverse::*;
+pub use multi::*;
+pub use parallel::*;
+pub use parser::*;
+pub use reference::*;
+pub use repair::*;
+pub use repl::*;
+pub use snapshot::*;
+pub use solver::*;
+pub use summary::*;
+pub use targets::*;
+pub use trajectory::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Left,
+    Right,
 }
 
-impl DialRotation {
-    pub fn new(direction:char, rotation_amount:i32) -> DialRotation {
+impl Direction {
+    pub fn from_char(direction:char) -> Option<Direction> {
+        match direction {
+            'L' => Some(Direction::Left),
+            'R' => Some(Direction::Right),
+            _ => None
+        }
+    }
+
+    pub fn as_char(&self) -> char {
+        match self {
+            Direction::Left => 'L',
+            Direction::Right => 'R',
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_char())
+    }
+}
+
+/// A rotation by a number of clicks held in `T`, which is `i32` unless a wider amount is needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DialRotation<T = i32> {
+    direction:Direction,
+    rotation_amount:T,
+}
+
+impl<T: DialInt> DialRotation<T> {
+    pub fn new(direction:Direction, rotation_amount:T) -> DialRotation<T> {
         DialRotation {direction, rotation_amount }
     }
 
-    pub fn get_direction(&self) -> char {
+    pub fn get_direction(&self) -> Direction {
         self.direction
     }
 
-    pub fn get_value(&self) -> i32 {
+    pub fn get_value(&self) -> T {
         self.rotation_amount
     }
+
+    /// The rotation amount with left turns negative, at any width. `None` only if negating the
+    /// amount overflows, which needs an `i128` amount of `i128::MIN`.
+    pub fn get_wide_signed_value(&self) -> Option<i128> {
+        match self.direction {
+            Direction::Left => self.rotation_amount.to_i128().checked_neg(),
+            Direction::Right => Some(self.rotation_amount.to_i128()),
+        }
+    }
 }
 
-pub struct Dial {
+impl DialRotation {
+    /// The rotation amount with left turns negative, as taken by `count_zero_clicks`.
+    pub fn get_signed_value(&self) -> i64 {
+        match self.direction {
+            Direction::Left => -(self.rotation_amount as i64),
+            Direction::Right => self.rotation_amount as i64,
+        }
+    }
+}
+
+impl<T: DialInt> fmt::Display for DialRotation<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.direction, self.rotation_amount)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseDialRotationErrorKind {
+    Empty,
+    InvalidDirection(char),
+    MissingAmount,
+    InvalidAmount,
+    AmountTooLarge,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDialRotationError {
+    text: String,
+    kind: ParseDialRotationErrorKind,
+}
+
+impl ParseDialRotationError {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn kind(&self) -> &ParseDialRotationErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for ParseDialRotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseDialRotationErrorKind::Empty => write!(f, "empty dial rotation"),
+            ParseDialRotationErrorKind::InvalidDirection(c) => {
+                write!(f, "invalid direction '{}' in dial rotation {:?} (expected 'L' or 'R')", c, self.text)
+            },
+            ParseDialRotationErrorKind::MissingAmount => write!(f, "missing rotation amount in dial rotation {:?}", self.text),
+            ParseDialRotationErrorKind::InvalidAmount => write!(f, "invalid rotation amount in dial rotation {:?}", self.text),
+            ParseDialRotationErrorKind::AmountTooLarge => write!(f, "rotation amount too large in dial rotation {:?}", self.text),
+        }
+    }
+}
+
+impl std::error::Error for ParseDialRotationError {}
+
+impl<T: DialInt> FromStr for DialRotation<T> {
+    type Err = ParseDialRotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |kind| ParseDialRotationError { text: s.to_string(), kind };
+        let trimmed = s.trim();
+
+        let mut chars = trimmed.chars();
+        let direction_char = chars.next().ok_or_else(|| error(ParseDialRotationErrorKind::Empty))?;
+        let direction = Direction::from_char(direction_char)
+            .ok_or_else(|| error(ParseDialRotationErrorKind::InvalidDirection(direction_char)))?;
+
+        let amount = chars.as_str();
+        if amount.is_empty() {
+            return Err(error(ParseDialRotationErrorKind::MissingAmount));
+        }
+        if !amount.chars().all(|c| c.is_ascii_digit()) {
+            return Err(error(ParseDialRotationErrorKind::InvalidAmount));
+        }
+        // Only digits are left, so the only way parsing can fail is the amount not fitting in `T`.
+        let rotation_amount = amount.parse::<T>().map_err(|_| error(ParseDialRotationErrorKind::AmountTooLarge))?;
+
+        Ok(DialRotation::new(direction, rotation_amount))
+    }
+}
+
+pub const DEFAULT_DIAL_SIZE: i32 = 100;
+
+/// A dial that counts zeros in `T`. Positions are always `i32`; `T` sets how large a rotation
+/// amount and a zero count can be.
+#[derive(Debug, Clone)]
+pub struct Dial<T = i32> {
+    size: i32,
     current_position: i32,
-    zero_count:i32
+    zero_count:T
 }
 
-impl Dial {
-    pub fn new(current_position:i32, zero_count:i32) -> Result<Dial, String> {
-        if current_position > 99 {
+impl<T: DialInt> Dial<T> {
+    pub fn new(current_position:i32, zero_count:T) -> Result<Dial<T>, String> {
+        Dial::with_size(DEFAULT_DIAL_SIZE, current_position, zero_count)
+    }
+
+    pub fn with_size(size:i32, current_position:i32, zero_count:T) -> Result<Dial<T>, String> {
+        if size < 1 {
+            return Err("Invalid dial size".to_string());
+        }
+        if current_position < 0 || current_position >= size {
             return Err("Invalid dial starting position".to_string());
         }
-        Ok(Dial {current_position, zero_count})
+        Ok(Dial {size, current_position, zero_count})
+    }
+
+    pub fn get_size(&self) -> i32 {
+        self.size
     }
 
     pub fn get_current_position(&self) -> i32 {
         self.current_position
     }
 
-    pub fn get_zero_count(&self) -> i32 {
+    pub fn get_zero_count(&self) -> T {
         self.zero_count
     }
 
-    pub fn turn_dial_in_direction_part1(&mut self, dial_rotation: &DialRotation) {
-        self.increment_zero_count_if_dial_at_zero();
+    /// Turns the dial and counts a zero if it finishes on 0. Fails, leaving the dial as it was,
+    /// if the count would overflow `T`.
+    pub fn turn_dial_in_direction_part1(&mut self, dial_rotation: &DialRotation<T>) -> Result<(), String> {
+        let movement = self.rotate(dial_rotation);
+        self.count_zeros(&movement, movement.landed_on_zero() as u128)
+    }
 
-        match dial_rotation.get_direction() {
-            'L' => {
-                self.set_dial_to_new_position((self.current_position - dial_rotation.get_value()).rem_euclid(100));
-            },
-            'R' => {
-                self.set_dial_to_new_position((self.current_position + dial_rotation.get_value()).rem_euclid(100));
-            },
-            _ => panic!("Invalid dial rotation (this should absolutely never happen given the input)")
-        }
+    /// Turns the dial and counts every click that lands on 0. Fails, leaving the dial as it was,
+    /// if the count would overflow `T`.
+    pub fn turn_dial_in_direction_part2(&mut self, dial_rotation: &DialRotation<T>) -> Result<(), String> {
+        let amount = Dial::<T>::wide_amount(dial_rotation)?;
+        let movement = self.rotate(dial_rotation);
+        self.count_zeros(&movement, count_zero_clicks_wide(movement.start as i128, amount, movement.size as i128))
+    }
+
+    pub fn set_dial_to_new_position(&mut self, new_position:i32) { self.current_position = new_position; }
+
+    pub fn dial_is_at_zero(&self) -> bool { self.current_position == 0 }
+
+    /// Moves the dial without counting anything, returning what the move looked like.
+    pub fn rotate(&mut self, dial_rotation: &DialRotation<T>) -> DialMovement<T> {
+        let movement = DialMovement::new(self.size, self.current_position, *dial_rotation);
+        self.set_dial_to_new_position(movement.end);
+        movement
     }
 
-    pub fn turn_dial_in_direction_part2(&mut self, dial_rotation: &DialRotation) {
-        self.increment_zero_count_if_dial_at_zero();
+    pub fn turn_dial_with<P: CountingPolicy<T>>(&mut self, dial_rotation: &DialRotation<T>, policy: &mut P) -> DialMovement<T> {
+        let movement = self.rotate(dial_rotation);
+        policy.observe(&movement);
+        movement
+    }
 
-        match dial_rotation.get_direction() {
-            'L' => {
-                self.increment_zero_count_by_full_rotations(dial_rotation);
-                self.increment_zero_count_if_dial_rotates_past_zero(dial_rotation);
-                self.set_dial_to_new_position((self.current_position - dial_rotation.get_value()).rem_euclid(100));
+    fn wide_amount(dial_rotation: &DialRotation<T>) -> Result<i128, String> {
+        dial_rotation
+            .get_wide_signed_value()
+            .ok_or_else(|| format!("Rotation amount {} is out of range", dial_rotation))
+    }
+
+    /// Adds `zeros` to the count, or puts the dial back where `movement` started if that overflows.
+    fn count_zeros(&mut self, movement: &DialMovement<T>, zeros: u128) -> Result<(), String> {
+        match T::from_u128(zeros).and_then(|zeros| self.zero_count.checked_add(zeros)) {
+            Some(zero_count) => {
+                self.zero_count = zero_count;
+                Ok(())
             },
-            'R' => {
-                self.increment_zero_count_by_full_rotations(dial_rotation);
-                self.increment_zero_count_if_dial_rotates_past_zero(dial_rotation);
-                self.set_dial_to_new_position((self.current_position + dial_rotation.get_value()).rem_euclid(100));
+            None => {
+                self.set_dial_to_new_position(movement.start);
+                Err(format!("Zero count overflowed {}", T::NAME))
             },
-            _ => panic!("Invalid dial rotation (this should absolutely never happen given the input)")
         }
     }
+}
 
-    pub fn set_dial_to_new_position(&mut self, new_position:i32) { self.current_position = new_position; }
+/// A single rotation applied to a dial of `size` positions, from `start` to `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DialMovement<T = i32> {
+    pub size: i32,
+    pub start: i32,
+    pub rotation: DialRotation<T>,
+    pub end: i32,
+}
 
-    pub fn count_full_rotations(&self, dial_rotation: &DialRotation) -> i32 {
-        (dial_rotation.get_value() as f32 / 100.0f32).floor() as i32
+impl<T: DialInt> DialMovement<T> {
+    /// Works out where `rotation` takes a dial of `size` positions that is at `start`.
+    pub fn new(size:i32, start:i32, rotation:DialRotation<T>) -> DialMovement<T> {
+        // Only the clicks past the last full turn move the dial. They are smaller than the size, but
+        // adding them to `start` can still pass `i32::MAX` on a large dial.
+        let clicks = rotation.get_value().to_i128() % size as i128;
+        let end = match rotation.get_direction() {
+            Direction::Left => (start as i128 - clicks).rem_euclid(size as i128),
+            Direction::Right => (start as i128 + clicks).rem_euclid(size as i128),
+        } as i32;
+        DialMovement { size, start, rotation, end }
     }
 
-    pub fn check_if_dial_rotates_past_zero(&self, dial_rotation: &DialRotation) -> bool {
-        if self.current_position == 0 { return false; }
+    pub fn landed_on_zero(&self) -> bool {
+        self.end == 0
+    }
+}
 
-        let value_after_full_rotations = dial_rotation.get_value().rem_euclid(100);
+/// Click counts, which the analysis modules and the `repl`, `animate` and `export` commands build
+/// on, are for `i32` amounts. Wider dials count clicks with `turn_dial_in_direction_part2`.
+impl DialMovement {
+    pub fn zero_clicks(&self) -> u64 {
+        count_zero_clicks(self.start as i64, self.rotation.get_signed_value(), self.size as i64)
+    }
+
+    pub fn full_turns(&self) -> u64 {
+        self.rotation.get_signed_value().unsigned_abs() / self.size as u64
+    }
 
-        match dial_rotation.get_direction() {
-            'L' => self.current_position - value_after_full_rotations < 0,
-            'R' => self.current_position + value_after_full_rotations > 100,
-            _ => panic!("Invalid dial rotation (this should absolutely never happen given the input)")
+    /// The clicks left over after the full turns, as the first position they reach and how many
+    /// positions they cover. Those positions run upwards from the first one, wrapping past the top.
+    /// A negative amount turns the other way, as it does in `zero_clicks`.
+    pub fn partial_arc(&self) -> Option<(i32, i32)> {
+        let amount = self.rotation.get_signed_value();
+        let clicks = (amount.unsigned_abs() % self.size as u64) as i32;
+        if clicks == 0 {
+            return None;
         }
+
+        let first_position = if amount > 0 {
+            (self.start + 1) % self.size
+        } else {
+            (self.start - clicks).rem_euclid(self.size)
+        };
+        Some((first_position, clicks))
     }
 
-    pub fn dial_is_at_zero(&mut self) -> bool { self.current_position == 0 }
+    pub fn target_clicks(&self, target:i32) -> u64 {
+        count_target_clicks(self.start as i64, self.rotation.get_signed_value(), target as i64, self.size as i64)
+    }
+}
 
-    pub fn increment_zero_count_if_dial_at_zero(&mut self) {
-        if self.dial_is_at_zero() {
-            self.zero_count += 1;
-        }
+/// Counts how many of the `amount.abs()` clicks of a rotation starting at `position` land on 0.
+/// A positive `amount` turns right and a negative one turns left. The starting position itself
+/// is not a click, so a rotation that starts on 0 only counts it again after a full turn.
+pub fn count_zero_clicks(position:i64, amount:i64, size:i64) -> u64 {
+    count_zero_clicks_wide(position as i128, amount as i128, size as i128) as u64
+}
+
+/// `count_zero_clicks` for amounts of any width. Working in `u128` keeps it exact even for
+/// `i128::MIN` clicks on a dial of one position.
+pub fn count_zero_clicks_wide(position:i128, amount:i128, size:i128) -> u128 {
+    let size = size as u128;
+    let position = position.rem_euclid(size as i128) as u128;
+    let clicks = amount.unsigned_abs();
+
+    let clicks_to_zero = if amount >= 0 { (size - position) % size } else { position };
+    let first_zero_click = if clicks_to_zero == 0 { size } else { clicks_to_zero };
+
+    if clicks < first_zero_click {
+        0
+    } else {
+        (clicks - first_zero_click) / size + 1
+    }
+}
+
+/// The same count as `count_zero_clicks`, for clicks that land on `target` instead of 0.
+pub fn count_target_clicks(position:i64, amount:i64, target:i64, size:i64) -> u64 {
+    let relative_position = (position as i128 - target as i128).rem_euclid(size as i128);
+    count_zero_clicks(relative_position as i64, amount, size)
+}
+
+pub fn get_dial_rotation(dial_rotation: &str) -> DialRotation {
+    dial_rotation.parse().unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// The example rotations from the puzzle, which give 3 and 6 from 50 and end on 32.
+#[cfg(test)]
+pub(crate) const TEST_INPUT: [&str; 10] = ["L68", "L30", "R48", "L5", "R60", "L55", "L1", "L99", "R14", "L82"];
+
+#[cfg(test)]
+pub(crate) fn test_rotations() -> Vec<DialRotation> {
+    TEST_INPUT.iter().map(|s| get_dial_rotation(s)).collect()
+}
+
+/// `count` rotations of up to 999 clicks either way, the same on every run for a given `seed`.
+#[cfg(test)]
+pub(crate) fn seeded_rotations(seed: u64, count: usize) -> Vec<DialRotation> {
+    let mut rng = GeneratorRng::new(seed);
+    (0..count)
+        .map(|_| {
+            let direction = if rng.chance(0.5) { Direction::Left } else { Direction::Right };
+            DialRotation::new(direction, rng.below(1000) as i32)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod parsing_tests {
+    use super::*;
+
+    #[test]
+    fn parsing_r300_gives_right_300() {
+        assert_eq!("R300".parse::<DialRotation>(), Ok(DialRotation::new(Direction::Right, 300)));
+    }
+
+    #[test]
+    fn parsing_ignores_trailing_carriage_return() {
+        assert_eq!("L5\r".parse::<DialRotation>(), Ok(DialRotation::new(Direction::Left, 5)));
+    }
+
+    #[test]
+    fn parsing_empty_line_is_an_error() {
+        let error = "".parse::<DialRotation>().unwrap_err();
+        assert_eq!(error.kind(), &ParseDialRotationErrorKind::Empty);
     }
 
-    pub fn increment_zero_count_by_full_rotations(&mut self, dial_rotation: &DialRotation) {
-        self.zero_count += self.count_full_rotations(dial_rotation);
+    #[test]
+    fn parsing_unknown_direction_is_an_error() {
+        let error = "X12".parse::<DialRotation>().unwrap_err();
+        assert_eq!(error.kind(), &ParseDialRotationErrorKind::InvalidDirection('X'));
+        assert_eq!(error.text(), "X12");
+    }
 
+    #[test]
+    fn parsing_direction_without_amount_is_an_error() {
+        let error = "R".parse::<DialRotation>().unwrap_err();
+        assert_eq!(error.kind(), &ParseDialRotationErrorKind::MissingAmount);
     }
 
-    pub fn increment_zero_count_if_dial_rotates_past_zero(&mut self, dial_rotation: &DialRotation) {
-        if self.check_if_dial_rotates_past_zero(dial_rotation) {
-            self.zero_count += 1;
+    #[test]
+    fn parsing_signed_or_non_numeric_amount_is_an_error() {
+        for text in ["L-5", "R+5", "L6B", "R 5"] {
+            let error = text.parse::<DialRotation>().unwrap_err();
+            assert_eq!(error.kind(), &ParseDialRotationErrorKind::InvalidAmount, "{}", text);
         }
     }
 
-}
+    #[test]
+    fn parsing_amount_that_does_not_fit_is_an_error() {
+        let error = "R3000000000".parse::<DialRotation>().unwrap_err();
+        assert_eq!(error.kind(), &ParseDialRotationErrorKind::AmountTooLarge);
+    }
 
-pub fn get_dial_rotation(dial_rotation: &str) -> DialRotation {
-    DialRotation {
-        direction:dial_rotation.chars().nth(0).unwrap(),
-        rotation_amount:dial_rotation.chars().skip(1).collect::<String>().parse::<i32>().unwrap()
+    #[test]
+    fn displaying_dial_rotation_round_trips() {
+        let dial_rotation = DialRotation::new(Direction::Left, 68);
+        assert_eq!(dial_rotation.to_string(), "L68");
+        assert_eq!(dial_rotation.to_string().parse::<DialRotation>(), Ok(dial_rotation));
     }
 }
 
@@ -124,7 +467,7 @@ mod part1_tests {
     #[test]
     fn getting_dial_rotation_input_works_part1() {
         let dial_rotation = get_dial_rotation("L68");
-        assert_eq!(dial_rotation.direction, 'L');
+        assert_eq!(dial_rotation.direction, Direction::Left);
         assert_eq!(dial_rotation.rotation_amount, 68);
     }
 
@@ -146,12 +489,36 @@ mod part1_tests {
         assert!(dial.is_ok());
     }
 
+    #[test]
+    fn new_dial_below_0_should_not_work_part1() {
+        let dial = Dial::new(-1, 0);
+        assert!(dial.is_err());
+    }
+
+    #[test]
+    fn new_dial_of_size_0_should_not_work_part1() {
+        let dial = Dial::with_size(0, 0, 0);
+        assert!(dial.is_err());
+    }
+
+    #[test]
+    fn new_dial_of_size_12_at_12_should_not_work_part1() {
+        let dial = Dial::with_size(12, 12, 0);
+        assert!(dial.is_err());
+    }
+
+    #[test]
+    fn new_dial_defaults_to_size_100_part1() {
+        let dial = Dial::new(50, 0).unwrap();
+        assert_eq!(dial.get_size(), 100);
+    }
+
     #[test]
     fn turning_dial_left_by_5_results_in_45_part1() {
         let dial_rotation = get_dial_rotation("L5");
         let mut dial = Dial::new(50, 0).unwrap();
 
-        dial.turn_dial_in_direction_part1(&dial_rotation);
+        dial.turn_dial_in_direction_part1(&dial_rotation).unwrap();
         let dial_result_position = dial.get_current_position();
 
         assert_eq!(dial_result_position, 45);
@@ -162,7 +529,7 @@ mod part1_tests {
         let dial_rotation = get_dial_rotation("R5");
         let mut dial = Dial::new(50, 0).unwrap();
 
-        dial.turn_dial_in_direction_part1(&dial_rotation);
+        dial.turn_dial_in_direction_part1(&dial_rotation).unwrap();
         let dial_result_position = dial.get_current_position();
 
         assert_eq!(dial_result_position, 55);
@@ -173,7 +540,7 @@ mod part1_tests {
         let dial_rotation = get_dial_rotation("L1");
         let mut dial = Dial::new(0, 0).unwrap();
 
-        dial.turn_dial_in_direction_part1(&dial_rotation);
+        dial.turn_dial_in_direction_part1(&dial_rotation).unwrap();
         let dial_result_position = dial.get_current_position();
 
         assert_eq!(dial_result_position, 99);
@@ -184,7 +551,7 @@ mod part1_tests {
         let dial_rotation = get_dial_rotation("R1");
         let mut dial = Dial::new(99, 0).unwrap();
 
-        dial.turn_dial_in_direction_part1(&dial_rotation);
+        dial.turn_dial_in_direction_part1(&dial_rotation).unwrap();
         let dial_result_position = dial.get_current_position();
 
         assert_eq!(dial_result_position, 0);
@@ -195,7 +562,7 @@ mod part1_tests {
         let dial_rotation = get_dial_rotation("L68");
         let mut dial = Dial::new(50, 0).unwrap();
 
-        dial.turn_dial_in_direction_part1(&dial_rotation);
+        dial.turn_dial_in_direction_part1(&dial_rotation).unwrap();
         let dial_result_position = dial.get_current_position();
 
         assert_eq!(dial_result_position, 82);
@@ -206,7 +573,7 @@ mod part1_tests {
         let dial_rotation = get_dial_rotation("R48");
         let mut dial = Dial::new(52, 0).unwrap();
 
-        dial.turn_dial_in_direction_part1(&dial_rotation);
+        dial.turn_dial_in_direction_part1(&dial_rotation).unwrap();
         let dial_result_position = dial.get_current_position();
 
         assert_eq!(dial_result_position, 0);
@@ -217,12 +584,53 @@ mod part1_tests {
         let dial_rotation = get_dial_rotation("R300");
         let mut dial = Dial::new(50, 0).unwrap();
 
-        dial.turn_dial_in_direction_part1(&dial_rotation);
+        dial.turn_dial_in_direction_part1(&dial_rotation).unwrap();
         let dial_result_position = dial.get_current_position();
 
         assert_eq!(dial_result_position, 50);
     }
 
+    #[test]
+    fn turning_dial_right_by_48_from_52_counts_zero_immediately_part1() {
+        let dial_rotation = get_dial_rotation("R48");
+        let mut dial = Dial::new(52, 0).unwrap();
+
+        dial.turn_dial_in_direction_part1(&dial_rotation).unwrap();
+
+        assert_eq!(dial.get_zero_count(), 1);
+    }
+
+    #[test]
+    fn turning_dial_away_from_zero_does_not_count_it_part1() {
+        let dial_rotation = get_dial_rotation("L5");
+        let mut dial = Dial::new(0, 0).unwrap();
+
+        dial.turn_dial_in_direction_part1(&dial_rotation).unwrap();
+
+        assert_eq!(dial.get_zero_count(), 0);
+    }
+
+    #[test]
+    fn turning_size_12_dial_right_by_5_from_10_results_in_3_part1() {
+        let dial_rotation = get_dial_rotation("R5");
+        let mut dial = Dial::with_size(12, 10, 0).unwrap();
+
+        dial.turn_dial_in_direction_part1(&dial_rotation).unwrap();
+        let dial_result_position = dial.get_current_position();
+
+        assert_eq!(dial_result_position, 3);
+    }
+
+    #[test]
+    fn turning_size_26_dial_left_by_27_from_0_results_in_25_part1() {
+        let dial_rotation = get_dial_rotation("L27");
+        let mut dial = Dial::with_size(26, 0, 0).unwrap();
+
+        dial.turn_dial_in_direction_part1(&dial_rotation).unwrap();
+        let dial_result_position = dial.get_current_position();
+
+        assert_eq!(dial_result_position, 25);
+    }
 }
 
 #[cfg(test)]
@@ -234,7 +642,7 @@ mod part2_tests {
         let dial_rotation = get_dial_rotation("R300");
         let mut dial = Dial::new(50, 0).unwrap();
 
-        dial.turn_dial_in_direction_part2(&dial_rotation);
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
         let dial_result_position = dial.get_current_position();
 
         assert_eq!(dial_result_position, 50);
@@ -245,7 +653,7 @@ mod part2_tests {
         let dial_rotation = get_dial_rotation("L300");
         let mut dial = Dial::new(10, 0).unwrap();
 
-        dial.turn_dial_in_direction_part2(&dial_rotation);
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
         let dial_result_position = dial.get_current_position();
 
         assert_eq!(dial_result_position, 10);
@@ -256,7 +664,7 @@ mod part2_tests {
         let dial_rotation = get_dial_rotation("L341");
         let mut dial = Dial::new(10, 0).unwrap();
 
-        dial.turn_dial_in_direction_part2(&dial_rotation);
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
         let dial_result_position = dial.get_current_position();
 
         assert_eq!(dial_result_position, 69);
@@ -267,7 +675,7 @@ mod part2_tests {
         let dial_rotation = get_dial_rotation("L50");
         let mut dial = Dial::new(99, 0).unwrap();
 
-        dial.turn_dial_in_direction_part2(&dial_rotation);
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
         let dial_result_position = dial.get_current_position();
 
         assert_eq!(dial_result_position, 49);
@@ -278,7 +686,7 @@ mod part2_tests {
         let dial_rotation = get_dial_rotation("R541");
         let mut dial = Dial::new(0, 0).unwrap();
 
-        dial.turn_dial_in_direction_part2(&dial_rotation);
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
         let dial_result_position = dial.get_current_position();
 
         assert_eq!(dial_result_position, 41);
@@ -289,8 +697,7 @@ mod part2_tests {
         let dial_rotation = get_dial_rotation("R399");
         let mut dial = Dial::new(50, 0).unwrap();
 
-        dial.increment_zero_count_by_full_rotations(&dial_rotation);
-        dial.increment_zero_count_if_dial_rotates_past_zero(&dial_rotation);
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
 
         assert_eq!(dial.get_zero_count(), 4);
     }
@@ -300,8 +707,7 @@ mod part2_tests {
         let dial_rotation = get_dial_rotation("R400");
         let mut dial = Dial::new(50, 0).unwrap();
 
-        dial.increment_zero_count_by_full_rotations(&dial_rotation);
-        dial.increment_zero_count_if_dial_rotates_past_zero(&dial_rotation);
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
 
         assert_eq!(dial.get_zero_count(), 4);
     }
@@ -311,8 +717,7 @@ mod part2_tests {
         let dial_rotation = get_dial_rotation("R499");
         let mut dial = Dial::new(99, 0).unwrap();
 
-        dial.increment_zero_count_by_full_rotations(&dial_rotation);
-        dial.increment_zero_count_if_dial_rotates_past_zero(&dial_rotation);
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
 
         assert_eq!(dial.get_zero_count(), 5);
     }
@@ -322,8 +727,7 @@ mod part2_tests {
         let dial_rotation = get_dial_rotation("L300");
         let mut dial = Dial::new(10, 0).unwrap();
 
-        dial.increment_zero_count_by_full_rotations(&dial_rotation);
-        dial.increment_zero_count_if_dial_rotates_past_zero(&dial_rotation);
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
 
         assert_eq!(dial.get_zero_count(), 3);
     }
@@ -333,8 +737,7 @@ mod part2_tests {
         let dial_rotation = get_dial_rotation("L341");
         let mut dial = Dial::new(10, 0).unwrap();
 
-        dial.increment_zero_count_by_full_rotations(&dial_rotation);
-        dial.increment_zero_count_if_dial_rotates_past_zero(&dial_rotation);
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
 
         assert_eq!(dial.get_zero_count(), 4);
     }
@@ -344,8 +747,7 @@ mod part2_tests {
         let dial_rotation = get_dial_rotation("L50");
         let mut dial = Dial::new(99, 0).unwrap();
 
-        dial.increment_zero_count_by_full_rotations(&dial_rotation);
-        dial.increment_zero_count_if_dial_rotates_past_zero(&dial_rotation);
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
 
         assert_eq!(dial.get_zero_count(), 0);
     }
@@ -355,11 +757,201 @@ mod part2_tests {
         let dial_rotation = get_dial_rotation("L5");
         let mut dial = Dial::new(0, 0).unwrap();
 
-        dial.increment_zero_count_by_full_rotations(&dial_rotation);
-        dial.increment_zero_count_if_dial_rotates_past_zero(&dial_rotation);
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
 
         assert_eq!(dial.get_zero_count(), 0);
     }
+
+    #[test]
+    fn turning_size_360_dial_right_by_750_from_300_should_pass_zero_2_times_part2() {
+        let dial_rotation = get_dial_rotation("R750");
+        let mut dial = Dial::with_size(360, 300, 0).unwrap();
+
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
+
+        assert_eq!(dial.get_zero_count(), 2);
+        assert_eq!(dial.get_current_position(), 330);
+    }
+
+    #[test]
+    fn turning_size_12_dial_left_by_30_from_5_results_in_11_part2() {
+        let dial_rotation = get_dial_rotation("L30");
+        let mut dial = Dial::with_size(12, 5, 0).unwrap();
+
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
+
+        assert_eq!(dial.get_current_position(), 11);
+        assert_eq!(dial.get_zero_count(), 3);
+    }
+
+    #[test]
+    fn turning_dial_right_by_50_from_50_counts_landing_on_zero_immediately_part2() {
+        let dial_rotation = get_dial_rotation("R50");
+        let mut dial = Dial::new(50, 0).unwrap();
+
+        dial.turn_dial_in_direction_part2(&dial_rotation).unwrap();
+
+        assert_eq!(dial.get_current_position(), 0);
+        assert_eq!(dial.get_zero_count(), 1);
+    }
+
+    #[test]
+    fn landing_on_zero_then_turning_right_by_100_counts_2_zeros_part2() {
+        let mut dial = Dial::new(50, 0).unwrap();
+
+        dial.turn_dial_in_direction_part2(&get_dial_rotation("R50")).unwrap();
+        dial.turn_dial_in_direction_part2(&get_dial_rotation("R100")).unwrap();
+        dial.turn_dial_in_direction_part2(&get_dial_rotation("R1")).unwrap();
+
+        assert_eq!(dial.get_zero_count(), 2);
+    }
+
+    #[test]
+    fn count_zero_clicks_matches_click_by_click_simulation_for_every_start_position() {
+        for size in (1..=13i64).chain([100, 360]) {
+            for position in 0..size {
+                for step in [-1, 1] {
+                    let mut simulated_position = position;
+                    let mut simulated_zero_clicks = 0;
+
+                    for clicks in 0..=3 * size + 1 {
+                        let amount = step * clicks;
+                        assert_eq!(
+                            count_zero_clicks(position, amount, size),
+                            simulated_zero_clicks,
+                            "size {size}, position {position}, amount {amount}"
+                        );
+
+                        simulated_position = (simulated_position + step).rem_euclid(size);
+                        if simulated_position == 0 {
+                            simulated_zero_clicks += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn count_zero_clicks_is_exact_for_extreme_amounts() {
+        assert_eq!(count_zero_clicks(0, i64::MAX, 100), 92_233_720_368_547_758);
+        assert_eq!(count_zero_clicks(0, i64::MIN, 100), 92_233_720_368_547_758);
+        assert_eq!(count_zero_clicks(8, i64::MIN, 100), 92_233_720_368_547_759);
+        assert_eq!(count_zero_clicks(0, i64::MIN, 1), 9_223_372_036_854_775_808);
+    }
+
+    #[test]
+    fn count_target_clicks_shifts_the_zero_crossing_arithmetic() {
+        assert_eq!(count_target_clicks(10, 250, 20, 100), 3);
+        assert_eq!(count_target_clicks(10, -250, 20, 100), 2);
+        assert_eq!(count_target_clicks(20, -100, 20, 100), 1);
+        assert_eq!(count_target_clicks(99, 1, 0, 100), count_zero_clicks(99, 1, 100));
+    }
 }
 
+#[cfg(test)]
+mod width_tests {
+    use super::*;
+
+    fn answers<T: DialInt>() -> (T, T) {
+        let mut part1 = Dial::<T>::new(50, T::default()).unwrap();
+        let mut part2 = Dial::<T>::new(50, T::default()).unwrap();
+        for text in TEST_INPUT {
+            let rotation = text.parse::<DialRotation<T>>().unwrap();
+            part1.turn_dial_in_direction_part1(&rotation).unwrap();
+            part2.turn_dial_in_direction_part2(&rotation).unwrap();
+        }
+        (part1.get_zero_count(), part2.get_zero_count())
+    }
+
+    #[test]
+    fn every_width_gives_the_same_answers_for_test_input() {
+        assert_eq!(answers::<i32>(), (3, 6));
+        assert_eq!(answers::<i64>(), (3, 6));
+        assert_eq!(answers::<u64>(), (3, 6));
+        assert_eq!(answers::<i128>(), (3, 6));
+    }
+
+    #[test]
+    fn amounts_too_large_for_i32_parse_at_wider_widths() {
+        assert_eq!("R3000000000".parse::<DialRotation<i64>>(), Ok(DialRotation::new(Direction::Right, 3_000_000_000i64)));
+        assert_eq!("L3000000000".parse::<DialRotation<u64>>(), Ok(DialRotation::new(Direction::Left, 3_000_000_000u64)));
+
+        let error = "R18446744073709551616".parse::<DialRotation<u64>>().unwrap_err();
+        assert_eq!(error.kind(), &ParseDialRotationErrorKind::AmountTooLarge);
+        assert!("R18446744073709551616".parse::<DialRotation<i128>>().is_ok());
+    }
+
+    #[test]
+    fn wide_amounts_count_exactly() {
+        let mut dial = Dial::<i64>::new(50, 0).unwrap();
+        dial.turn_dial_in_direction_part2(&"R3000000000".parse().unwrap()).unwrap();
+        assert_eq!((dial.get_current_position(), dial.get_zero_count()), (50, 30_000_000));
+
+        let mut dial = Dial::<u64>::new(0, 0).unwrap();
+        dial.turn_dial_in_direction_part2(&DialRotation::new(Direction::Left, u64::MAX)).unwrap();
+        assert_eq!((dial.get_current_position(), dial.get_zero_count()), (85, u64::MAX / 100));
+    }
+
+    #[test]
+    fn overflowing_the_zero_count_is_an_error_that_leaves_the_dial_alone() {
+        let mut dial = Dial::<i32>::with_size(1, 0, 0).unwrap();
+        let rotation = DialRotation::new(Direction::Right, i32::MAX);
 
+        dial.turn_dial_in_direction_part2(&rotation).unwrap();
+        assert_eq!(dial.get_zero_count(), i32::MAX);
+
+        assert_eq!(dial.turn_dial_in_direction_part2(&rotation), Err("Zero count overflowed i32".to_string()));
+        assert_eq!(dial.turn_dial_in_direction_part1(&DialRotation::new(Direction::Left, 1)), Err("Zero count overflowed i32".to_string()));
+        assert_eq!(dial.get_zero_count(), i32::MAX);
+    }
+
+    #[test]
+    fn wide_dials_rotate_and_count_with_policies() {
+        let mut dial = Dial::<i64>::new(50, 0).unwrap();
+        let mut landings = LandingsOnZero::default();
+        let mut reversals = DirectionReversals::default();
+
+        for text in ["R3000000050", "L5000000000", "L100"] {
+            let rotation = text.parse::<DialRotation<i64>>().unwrap();
+            dial.clone().turn_dial_with(&rotation, &mut reversals);
+            dial.turn_dial_with(&rotation, &mut landings);
+        }
+
+        assert_eq!(dial.get_current_position(), 0);
+        assert_eq!((landings.count(), reversals.count()), (3, 1));
+    }
+
+    #[test]
+    fn movements_of_any_width_end_in_the_right_place() {
+        assert_eq!(DialMovement::new(100, 50, DialRotation::new(Direction::Right, u64::MAX)).end, 65);
+        assert_eq!(DialMovement::new(7, 3, DialRotation::new(Direction::Left, i128::MAX)).end, (3 - (i128::MAX % 7) as i32).rem_euclid(7));
+        assert!(Dial::<u64>::new(99, 0).unwrap().rotate(&DialRotation::new(Direction::Right, 1)).landed_on_zero());
+    }
+
+    #[test]
+    fn dials_larger_than_half_of_i32_rotate_without_overflowing() {
+        let rotation = DialRotation::new(Direction::Right, 1_999_999_999);
+        let mut dial = Dial::with_size(2_000_000_000, 1_999_999_999, 0).unwrap();
+        assert_eq!(dial.clone().rotate(&rotation).end, 1_999_999_998);
+
+        dial.turn_dial_in_direction_part2(&rotation).unwrap();
+        assert_eq!((dial.get_current_position(), dial.get_zero_count()), (1_999_999_998, 1));
+
+        dial.turn_dial_in_direction_part1(&DialRotation::new(Direction::Left, i32::MAX)).unwrap();
+        assert_eq!((dial.get_current_position(), dial.get_zero_count()), (1_852_516_351, 1));
+    }
+
+    #[test]
+    fn i128_counts_up_to_its_maximum() {
+        let mut dial = Dial::<i128>::with_size(7, 3, 0).unwrap();
+
+        dial.turn_dial_in_direction_part2(&DialRotation::new(Direction::Left, i128::MAX)).unwrap();
+        assert_eq!(dial.get_zero_count(), (i128::MAX - 3) / 7 + 1);
+
+        let mut dial = Dial::<i128>::with_size(1, 0, 1).unwrap();
+        dial.turn_dial_in_direction_part2(&DialRotation::new(Direction::Right, i128::MAX - 1)).unwrap();
+        assert_eq!(dial.get_zero_count(), i128::MAX);
+        assert!(dial.turn_dial_in_direction_part2(&DialRotation::new(Direction::Right, 1)).is_err());
+    }
+}
@@ -1,65 +1,139 @@
 use day1::*;
-use std::fs::read_to_string;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::process;
 
 fn main() {
-    // part1();
-    part2();
+    match std::env::args().nth(1).as_deref() {
+        None => {
+            // part1();
+            part2();
+        },
+        Some("repl") => repl(),
+        Some("animate") => animate(),
+        Some("export") => export(),
+        Some("generate") => generate(),
+        Some(other) => {
+            eprintln!(
+                "Unknown subcommand {:?}. Usage: day1 [repl | animate {} | export <output stem> [rotations file] | generate <output file> {}]",
+                other,
+                AnimationOptions::USAGE,
+                GeneratorConfig::USAGE
+            );
+            process::exit(2);
+        },
+    }
+}
+
+fn read_input() -> Vec<DialRotation<i64>> {
+    load_dial_rotations_as("input/input.txt").unwrap_or_else(|error| panic!("{}", error))
 }
 
+#[allow(dead_code)]
 fn part1() {
     // Part 1
     let mut dial = Dial::new(50, 0).unwrap();
-    let mut total_zeros_count = 0;
-
-    let test_input = read_to_string("input/input.txt").unwrap();
-
-    let dial_rotation_strings = test_input.split('\n').collect::<Vec<&str>>();
-
-    let mut dial_rotations:Vec<DialRotation> = vec![];
 
-    for s in dial_rotation_strings {
-        let dial_rotation = get_dial_rotation(s);
-        dial_rotations.push(dial_rotation);
+    for r in read_input() {
+        dial.turn_dial_in_direction_part1(&r).unwrap_or_else(|error| panic!("{}", error));
     }
 
-    for r in dial_rotations {
-        dial.turn_dial_in_direction_part1(&r);
-        if dial.get_current_position() == 0 {
-            total_zeros_count += 1;
-        }
-    }
-
-    println!("(Part 1): Total zeros count: {}", total_zeros_count);
+    println!("(Part 1): Total zeros count: {}", dial.get_zero_count());
 }
 
 fn part2() {
-    let test_input = read_to_string("input/input.txt").unwrap();
-
-    let dial_rotation_strings = test_input.split('\n').collect::<Vec<&str>>();
-
-    let mut dial_rotations:Vec<DialRotation> = vec![];
+    let mut dial = Dial::new(50, 0).unwrap();
 
-    for s in dial_rotation_strings {
-        let dial_rotation = get_dial_rotation(s);
-        dial_rotations.push(dial_rotation);
+    for r in read_input() {
+        dial.turn_dial_in_direction_part2(&r).unwrap_or_else(|error| panic!("{}", error));
     }
 
-    let mut dial = Dial::new(50, 0).unwrap();
+    println!("(Part 2): Total zeros count: {}", dial.get_zero_count());
+}
 
-    let test_input = read_to_string("input/input.txt").unwrap();
+fn repl() {
+    let mut session = ReplSession::default();
 
-    let dial_rotation_strings = test_input.split('\n').collect::<Vec<&str>>();
+    if let Err(error) = run_repl(&mut session, io::stdin().lock(), io::stdout()) {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
 
-    let mut dial_rotations:Vec<DialRotation> = vec![];
+fn animate() {
+    let (options, rest) = AnimationOptions::parse_args(std::env::args().skip(2)).unwrap_or_else(|error| {
+        eprintln!("{}. Usage: day1 animate {}", error, AnimationOptions::USAGE);
+        process::exit(2);
+    });
+    let path = rest.first().map(String::as_str).unwrap_or("input/input.txt");
+    let rotations = load_dial_rotations(path).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
 
-    for s in dial_rotation_strings {
-        let dial_rotation = get_dial_rotation(s);
-        dial_rotations.push(dial_rotation);
+    let mut dial = Dial::new(50, 0).unwrap();
+    let mut animator = Animator::new(io::stdout(), io::stdin().lock(), options);
+    let result = animator
+        .start(&dial)
+        .and_then(|_| rotations.iter().try_for_each(|rotation| animator.animate(&mut dial, rotation).map(drop)));
+
+    if let Err(error) = animator.finish().and(result) {
+        eprintln!("{}", error);
+        process::exit(1);
     }
+}
 
-    for r in dial_rotations {
-        dial.turn_dial_in_direction_part2(&r);
+fn export() {
+    let args = std::env::args().skip(2).collect::<Vec<_>>();
+    let Some(stem) = args.first() else {
+        eprintln!("Usage: day1 export <output stem> [rotations file]");
+        process::exit(2);
+    };
+    let path = args.get(1).map(String::as_str).unwrap_or("input/input.txt");
+    let rotations = load_dial_rotations(path).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    let dial = Dial::new(50, 0).unwrap();
+
+    let samples = TrajectorySamples::from_steps(dial.get_size(), trajectory(dial.clone(), &rotations), 1000);
+    let heatmap = VisitHeatmap::from_steps(dial.get_size(), trajectory(dial.clone(), &rotations), 1000);
+
+    let result = File::create(format!("{}.svg", stem))
+        .and_then(|file| write_trajectory_svg(&samples, BufWriter::new(file)))
+        .and_then(|_| File::create(format!("{}.ppm", stem)))
+        .and_then(|file| write_heatmap_ppm(&heatmap, BufWriter::new(file)));
+    match result {
+        Ok(()) => println!("Wrote {0}.svg and {0}.ppm", stem),
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        },
     }
+}
 
-    println!("(Part 2): Total zeros count: {}", dial.get_zero_count());
-}
\ No newline at end of file
+fn generate() {
+    let (config, rest) = GeneratorConfig::parse_args(std::env::args().skip(2)).unwrap_or_else(|error| {
+        eprintln!("{}. Usage: day1 generate <output file> {}", error, GeneratorConfig::USAGE);
+        process::exit(2);
+    });
+    let Some(path) = rest.first() else {
+        eprintln!("Usage: day1 generate <output file> {}", GeneratorConfig::USAGE);
+        process::exit(2);
+    };
+
+    match write_generated_input(&config, path) {
+        Ok(answers) => println!(
+            "Wrote {} rotations to {} (part 1: {}, part 2: {}, answers in {})",
+            answers.rotations,
+            path,
+            answers.part1,
+            answers.part2,
+            answers_path(path).display()
+        ),
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        },
+    }
+}
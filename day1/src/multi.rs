@@ -0,0 +1,258 @@
+use crate::{CountingRule, Dial, DialRotation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DialCounts {
+    pub size: i32,
+    pub end_position: i32,
+    pub zero_landings: u64,
+    pub zero_clicks: u64,
+}
+
+impl DialCounts {
+    pub fn count(&self, rule: CountingRule) -> u64 {
+        match rule {
+            CountingRule::LandingsOnZero => self.zero_landings,
+            CountingRule::ClicksThroughZero => self.zero_clicks,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiDialReport {
+    pub dial_counts: Vec<DialCounts>,
+    /// Indices of the rotations after which every dial was on 0.
+    pub simultaneous_zero_steps: Vec<usize>,
+}
+
+/// The first point of an endlessly repeated rotation stream where every dial is on 0: after
+/// rotation `step` of replay `replay`, which is rotation `global_step` of the whole stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimultaneousZero {
+    pub replay: u128,
+    pub step: usize,
+    pub global_step: u128,
+}
+
+/// Several independent dials, possibly of different sizes, all driven by the same rotations.
+pub struct MultiDialRunner {
+    dials: Vec<Dial>,
+}
+
+impl MultiDialRunner {
+    pub fn new(dials: Vec<Dial>) -> Result<MultiDialRunner, String> {
+        if dials.is_empty() {
+            return Err("A multi-dial runner needs at least one dial".to_string());
+        }
+        Ok(MultiDialRunner { dials })
+    }
+
+    pub fn dials(&self) -> &[Dial] {
+        &self.dials
+    }
+
+    pub fn run(&self, rotations: &[DialRotation]) -> MultiDialReport {
+        let mut dials = self.dials.clone();
+        let mut dial_counts = dials
+            .iter()
+            .map(|dial| DialCounts { size: dial.get_size(), end_position: dial.get_current_position(), zero_landings: 0, zero_clicks: 0 })
+            .collect::<Vec<_>>();
+        let mut simultaneous_zero_steps = Vec::new();
+
+        for (step, rotation) in rotations.iter().enumerate() {
+            for (dial, counts) in dials.iter_mut().zip(dial_counts.iter_mut()) {
+                let movement = dial.rotate(rotation);
+                counts.end_position = movement.end;
+                counts.zero_landings += CountingRule::LandingsOnZero.count_movement(&movement);
+                counts.zero_clicks += CountingRule::ClicksThroughZero.count_movement(&movement);
+            }
+            if dials.iter().all(Dial::dial_is_at_zero) {
+                simultaneous_zero_steps.push(step);
+            }
+        }
+
+        MultiDialReport { dial_counts, simultaneous_zero_steps }
+    }
+
+    /// Finds the first time every dial is on 0 when `rotations` is replayed forever.
+    ///
+    /// After replay `r` and rotation `k`, a dial of size `n` is at `start + r * net + prefix(k)`
+    /// modulo `n`, where `net` is one replay's displacement. Requiring that to be 0 gives one
+    /// linear congruence in `r` per dial; these are combined Chinese-Remainder style for each
+    /// `k`, and the earliest solution over all `k` wins. Returns `None` if it never happens.
+    pub fn first_simultaneous_zero_repeating(&self, rotations: &[DialRotation]) -> Result<Option<SimultaneousZero>, String> {
+        if rotations.is_empty() {
+            return Ok(None);
+        }
+
+        let net_displacements = self
+            .dials
+            .iter()
+            .map(|dial| {
+                let size = dial.get_size() as i128;
+                rotations.iter().map(|r| r.get_signed_value() as i128).sum::<i128>().rem_euclid(size)
+            })
+            .collect::<Vec<_>>();
+        let mut positions = self.dials.iter().map(|dial| dial.get_current_position() as i128).collect::<Vec<_>>();
+        let mut best: Option<SimultaneousZero> = None;
+
+        for (step, rotation) in rotations.iter().enumerate() {
+            let mut replays = Some(Congruence { residue: 0, modulus: 1 });
+            for ((dial, position), net) in self.dials.iter().zip(positions.iter_mut()).zip(&net_displacements) {
+                let size = dial.get_size() as i128;
+                *position = (*position + rotation.get_signed_value() as i128).rem_euclid(size);
+
+                replays = match (replays, solve_linear_congruence(*net, -*position, size)) {
+                    (Some(combined), Some(congruence)) => combine_congruences(combined, congruence)?,
+                    _ => None,
+                };
+            }
+
+            if let Some(replays) = replays {
+                let global_step = replays
+                    .residue
+                    .checked_mul(rotations.len() as i128)
+                    .and_then(|s| s.checked_add(step as i128))
+                    .ok_or_else(|| "Simultaneous zero is too far away to represent".to_string())?;
+                if best.is_none_or(|best| (global_step as u128) < best.global_step) {
+                    best = Some(SimultaneousZero { replay: replays.residue as u128, step, global_step: global_step as u128 });
+                }
+            }
+        }
+
+        Ok(best)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Congruence {
+    residue: i128,
+    modulus: i128,
+}
+
+/// Solves `a * r = b (mod n)` for `r`.
+fn solve_linear_congruence(a: i128, b: i128, n: i128) -> Option<Congruence> {
+    let (a, b) = (a.rem_euclid(n), b.rem_euclid(n));
+    let (g, x, _) = extended_gcd(a, n);
+    if b % g != 0 {
+        return None;
+    }
+
+    let modulus = n / g;
+    Some(Congruence { residue: ((b / g) % modulus * x.rem_euclid(modulus)).rem_euclid(modulus), modulus })
+}
+
+fn combine_congruences(first: Congruence, second: Congruence) -> Result<Option<Congruence>, String> {
+    let (g, x, _) = extended_gcd(first.modulus, second.modulus);
+    let difference = second.residue - first.residue;
+    if difference % g != 0 {
+        return Ok(None);
+    }
+
+    let second_reduced = second.modulus / g;
+    let too_large = || "Dial sizes are too large to combine".to_string();
+    let modulus = first.modulus.checked_mul(second_reduced).ok_or_else(too_large)?;
+    let steps = ((difference / g) % second_reduced)
+        .checked_mul(x.rem_euclid(second_reduced))
+        .ok_or_else(too_large)?
+        .rem_euclid(second_reduced);
+    let residue = (first.residue + first.modulus.checked_mul(steps).ok_or_else(too_large)?).rem_euclid(modulus);
+
+    Ok(Some(Congruence { residue, modulus }))
+}
+
+/// Returns `(g, x, y)` with `a * x + b * y = g = gcd(a, b)`.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_dial_rotation, test_rotations};
+
+    fn rotations(texts: &[&str]) -> Vec<DialRotation> {
+        texts.iter().map(|s| get_dial_rotation(s)).collect()
+    }
+
+    fn brute_force_first_simultaneous_zero(dials: &[Dial], rotations: &[DialRotation], max_replays: usize) -> Option<u128> {
+        let mut dials = dials.to_vec();
+        for replay in 0..max_replays {
+            for (step, rotation) in rotations.iter().enumerate() {
+                dials.iter_mut().for_each(|dial| { dial.rotate(rotation); });
+                if dials.iter().all(Dial::dial_is_at_zero) {
+                    return Some((replay * rotations.len() + step) as u128);
+                }
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn each_dial_gets_its_own_counts() {
+        let runner = MultiDialRunner::new(vec![Dial::new(50, 0).unwrap(), Dial::with_size(12, 0, 0).unwrap()]).unwrap();
+
+        let report = runner.run(&test_rotations());
+
+        assert_eq!(report.dial_counts[0], DialCounts { size: 100, end_position: 32, zero_landings: 3, zero_clicks: 6 });
+        assert_eq!(report.dial_counts[1].size, 12);
+        assert_eq!(report.dial_counts[1].end_position, (-218i32).rem_euclid(12));
+    }
+
+    #[test]
+    fn simultaneous_zero_steps_need_every_dial_on_zero() {
+        let runner = MultiDialRunner::new(vec![Dial::with_size(4, 0, 0).unwrap(), Dial::with_size(6, 0, 0).unwrap()]).unwrap();
+
+        let report = runner.run(&rotations(&["R4", "R8", "L12", "R24", "L1"]));
+
+        assert_eq!(report.simultaneous_zero_steps, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn first_simultaneous_zero_matches_brute_force() {
+        let cases: [(&[i32], &[&str]); 5] = [
+            (&[4, 6], &["R1"]),
+            (&[7, 9, 10], &["R3", "L1"]),
+            (&[12, 8], &["L5", "R2", "R7"]),
+            (&[100, 12], &["L68", "L30", "R48", "L5", "R60"]),
+            (&[6, 4], &["R2"]),
+        ];
+
+        for (sizes, texts) in cases {
+            let dials = sizes.iter().map(|&size| Dial::with_size(size, 1 % size, 0).unwrap()).collect::<Vec<_>>();
+            let rotations = rotations(texts);
+            let runner = MultiDialRunner::new(dials.clone()).unwrap();
+
+            let found = runner.first_simultaneous_zero_repeating(&rotations).unwrap();
+
+            assert_eq!(found.map(|z| z.global_step), brute_force_first_simultaneous_zero(&dials, &rotations, 2_000), "{:?} {:?}", sizes, texts);
+        }
+    }
+
+    #[test]
+    fn impossible_simultaneous_zero_is_none() {
+        // Both dials move in even steps, but one starts on an odd position.
+        let runner = MultiDialRunner::new(vec![Dial::with_size(4, 1, 0).unwrap(), Dial::with_size(6, 0, 0).unwrap()]).unwrap();
+
+        assert_eq!(runner.first_simultaneous_zero_repeating(&rotations(&["R2", "L4"])).unwrap(), None);
+    }
+
+    #[test]
+    fn far_away_simultaneous_zero_is_found_without_simulating() {
+        let sizes = [997, 991, 983, 977];
+        let runner = MultiDialRunner::new(sizes.iter().map(|&size| Dial::with_size(size, 1, 0).unwrap()).collect()).unwrap();
+
+        let found = runner.first_simultaneous_zero_repeating(&rotations(&["L1"])).unwrap().unwrap();
+
+        // Every dial reaches 0 after 1 click and then every `size` clicks.
+        let lcm = sizes.iter().map(|&size| size as u128).product::<u128>();
+        assert_eq!(found.global_step, 0);
+        assert_eq!(found.replay, 0);
+        let later = MultiDialRunner::new(sizes.iter().map(|&size| Dial::with_size(size, 0, 0).unwrap()).collect()).unwrap();
+        assert_eq!(later.first_simultaneous_zero_repeating(&rotations(&["R1"])).unwrap().unwrap().global_step, lcm - 1);
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{Dial, DialRotation, RangeOutcome, RotationSummary};
+use std::num::NonZeroUsize;
+use std::thread;
+
+/// Evaluates both counting rules over `rotations` from `dial`'s position on `threads` worker
+/// threads (0 means one per available core).
+///
+/// Each worker summarises a contiguous chunk for every start position at once, then the chunk
+/// summaries are composed in order. The result is exactly what turning `dial` one rotation at
+/// a time would give.
+pub fn evaluate_in_parallel(dial: &Dial, rotations: &[DialRotation], threads: usize) -> RangeOutcome {
+    let size = dial.get_size();
+    let threads = match threads {
+        0 => thread::available_parallelism().map(NonZeroUsize::get).unwrap_or(1),
+        threads => threads,
+    };
+    let chunk_len = rotations.len().div_ceil(threads).max(1);
+
+    let summaries = thread::scope(|scope| {
+        let workers = rotations
+            .chunks(chunk_len)
+            .map(|chunk| scope.spawn(move || RotationSummary::from_rotations(size, chunk)))
+            .collect::<Vec<_>>();
+
+        // `size` comes from a dial, so it is always a valid size.
+        workers.into_iter().map(|worker| worker.join().unwrap().unwrap()).collect::<Vec<_>>()
+    });
+
+    summaries
+        .iter()
+        .fold(RangeOutcome::starting_at(dial.get_current_position()), |outcome, summary| outcome.then(summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seeded_rotations;
+
+    #[test]
+    fn parallel_evaluation_matches_sequential_dial_for_both_parts() {
+        let rotations = seeded_rotations(12, 5_000);
+
+        for threads in [1, 2, 3, 7, 64] {
+            for start in [0, 50, 99] {
+                let mut part1 = Dial::new(start, 0).unwrap();
+                let mut part2 = Dial::new(start, 0).unwrap();
+                for rotation in &rotations {
+                    part1.turn_dial_in_direction_part1(rotation).unwrap();
+                    part2.turn_dial_in_direction_part2(rotation).unwrap();
+                }
+
+                let outcome = evaluate_in_parallel(&Dial::new(start, 0).unwrap(), &rotations, threads);
+
+                assert_eq!(outcome.zero_landings, part1.get_zero_count() as u64, "{} threads from {}", threads, start);
+                assert_eq!(outcome.zero_clicks, part2.get_zero_count() as u64, "{} threads from {}", threads, start);
+                assert_eq!(outcome.end_position, part2.get_current_position());
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_evaluation_of_no_rotations_stays_put() {
+        let outcome = evaluate_in_parallel(&Dial::with_size(12, 7, 0).unwrap(), &[], 0);
+
+        assert_eq!(outcome, RangeOutcome::starting_at(7));
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{DialInt, DialRotation, ParseDialRotationError, ParseDialRotationErrorKind};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Stop at the first line that is not a valid rotation.
+    Strict,
+    /// Skip bad lines, recording a diagnostic for each one.
+    Lenient,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationDiagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub error: ParseDialRotationError,
+}
+
+impl fmt::Display for RotationDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.error)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ParsedRotations<T = i32> {
+    pub rotations: Vec<DialRotation<T>>,
+    pub diagnostics: Vec<RotationDiagnostic>,
+}
+
+impl<T> ParsedRotations<T> {
+    pub fn is_clean(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+/// Parses one rotation per line. Blank lines and anything after a `#` are ignored, and both
+/// `\n` and `\r\n` line endings are accepted. Lines and columns in diagnostics are 1-based.
+pub fn parse_dial_rotations<R: BufRead>(reader: R, mode: ParseMode) -> io::Result<ParsedRotations> {
+    parse_dial_rotations_as(reader, mode)
+}
+
+/// `parse_dial_rotations` with amounts read as `T`, so amounts too large for an `i32` can be read.
+pub fn parse_dial_rotations_as<T: DialInt, R: BufRead>(mut reader: R, mode: ParseMode) -> io::Result<ParsedRotations<T>> {
+    let mut parsed = ParsedRotations::default();
+    let mut buffer = String::new();
+    let mut line = 0;
+
+    loop {
+        buffer.clear();
+        if reader.read_line(&mut buffer)? == 0 {
+            break;
+        }
+        line += 1;
+
+        let without_newline = buffer.strip_suffix('\n').unwrap_or(&buffer);
+        let without_newline = without_newline.strip_suffix('\r').unwrap_or(without_newline);
+        let content = match without_newline.find('#') {
+            Some(comment_start) => &without_newline[..comment_start],
+            None => without_newline,
+        };
+
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        match content.parse::<DialRotation<T>>() {
+            Ok(rotation) => parsed.rotations.push(rotation),
+            Err(error) => {
+                let column = error_column(content, error.kind());
+                parsed.diagnostics.push(RotationDiagnostic { line, column, error });
+                if mode == ParseMode::Strict {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Reads a whole rotation file in strict mode, reporting the first bad line as an error.
+pub fn load_dial_rotations<P: AsRef<Path>>(path: P) -> Result<Vec<DialRotation>, String> {
+    load_dial_rotations_as(path)
+}
+
+pub fn load_dial_rotations_as<T: DialInt, P: AsRef<Path>>(path: P) -> Result<Vec<DialRotation<T>>, String> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+    let parsed = parse_dial_rotations_as(BufReader::new(file), ParseMode::Strict)
+        .map_err(|error| format!("{}: {}", path.display(), error))?;
+
+    match parsed.diagnostics.first() {
+        Some(diagnostic) => Err(format!("{}: {}", path.display(), diagnostic)),
+        None => Ok(parsed.rotations),
+    }
+}
+
+fn error_column(content: &str, kind: &ParseDialRotationErrorKind) -> usize {
+    let leading_whitespace = content.chars().take_while(|c| c.is_whitespace()).count();
+    let direction_column = leading_whitespace + 1;
+
+    match kind {
+        ParseDialRotationErrorKind::Empty | ParseDialRotationErrorKind::InvalidDirection(_) => direction_column,
+        ParseDialRotationErrorKind::MissingAmount | ParseDialRotationErrorKind::AmountTooLarge => direction_column + 1,
+        ParseDialRotationErrorKind::InvalidAmount => {
+            let first_bad_digit = content.trim().chars().skip(1).take_while(|c| c.is_ascii_digit()).count();
+            direction_column + 1 + first_bad_digit
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Direction;
+
+    #[test]
+    fn parsing_handles_crlf_blank_lines_and_comments() {
+        let document = "# combination\r\nL68\r\n\r\nR48 # lands on zero\r\n   \nL5";
+
+        let parsed = parse_dial_rotations(document.as_bytes(), ParseMode::Strict).unwrap();
+
+        assert!(parsed.is_clean());
+        assert_eq!(parsed.rotations, vec![
+            DialRotation::new(Direction::Left, 68),
+            DialRotation::new(Direction::Right, 48),
+            DialRotation::new(Direction::Left, 5),
+        ]);
+    }
+
+    #[test]
+    fn strict_mode_stops_at_first_bad_line() {
+        let document = "L68\nX30\nR48\nL5Q\n";
+
+        let parsed = parse_dial_rotations(document.as_bytes(), ParseMode::Strict).unwrap();
+
+        assert_eq!(parsed.rotations, vec![DialRotation::new(Direction::Left, 68)]);
+        assert_eq!(parsed.diagnostics.len(), 1);
+        assert_eq!(parsed.diagnostics[0].line, 2);
+        assert_eq!(parsed.diagnostics[0].column, 1);
+    }
+
+    #[test]
+    fn lenient_mode_skips_bad_lines_and_reports_each_one() {
+        let document = "L68\nX30\nR48\n  L5Q\nR\n";
+
+        let parsed = parse_dial_rotations(document.as_bytes(), ParseMode::Lenient).unwrap();
+
+        assert_eq!(parsed.rotations, vec![
+            DialRotation::new(Direction::Left, 68),
+            DialRotation::new(Direction::Right, 48),
+        ]);
+        let positions = parsed.diagnostics.iter().map(|d| (d.line, d.column)).collect::<Vec<_>>();
+        assert_eq!(positions, vec![(2, 1), (4, 5), (5, 2)]);
+        assert_eq!(parsed.diagnostics[1].error.kind(), &ParseDialRotationErrorKind::InvalidAmount);
+    }
+
+    #[test]
+    fn diagnostics_display_line_and_column() {
+        let parsed = parse_dial_rotations("\nR4x".as_bytes(), ParseMode::Strict).unwrap();
+
+        assert_eq!(
+            parsed.diagnostics[0].to_string(),
+            "line 2, column 3: invalid rotation amount in dial rotation \"R4x\""
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{DialRotation, Direction};
+
+/// A dial that turns one click at a time and counts zeros as it goes. It shares none of the
+/// arithmetic in `Dial`, so it can check that arithmetic, at the cost of being O(clicks).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceDial {
+    size: i32,
+    position: i32,
+    zero_landings: u64,
+    zero_clicks: u64,
+}
+
+impl ReferenceDial {
+    pub fn new(size: i32, position: i32) -> Result<ReferenceDial, String> {
+        if size < 1 {
+            return Err("Invalid dial size".to_string());
+        }
+        if !(0..size).contains(&position) {
+            return Err("Invalid dial starting position".to_string());
+        }
+
+        Ok(ReferenceDial { size, position, zero_landings: 0, zero_clicks: 0 })
+    }
+
+    pub fn position(&self) -> i32 {
+        self.position
+    }
+
+    /// Rotations that finished on 0 (part 1).
+    pub fn zero_landings(&self) -> u64 {
+        self.zero_landings
+    }
+
+    /// Clicks that arrived at 0 (part 2).
+    pub fn zero_clicks(&self) -> u64 {
+        self.zero_clicks
+    }
+
+    pub fn turn(&mut self, rotation: &DialRotation) {
+        for _ in 0..rotation.get_value() {
+            self.position = match rotation.get_direction() {
+                Direction::Right if self.position == self.size - 1 => 0,
+                Direction::Right => self.position + 1,
+                Direction::Left if self.position == 0 => self.size - 1,
+                Direction::Left => self.position - 1,
+            };
+            if self.position == 0 {
+                self.zero_clicks += 1;
+            }
+        }
+        if self.position == 0 {
+            self.zero_landings += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_dial_rotation, test_rotations};
+
+    #[test]
+    fn reference_dial_gives_both_answers_for_test_input() {
+        let mut dial = ReferenceDial::new(100, 50).unwrap();
+
+        for rotation in test_rotations() {
+            dial.turn(&rotation);
+        }
+
+        assert_eq!((dial.position(), dial.zero_landings(), dial.zero_clicks()), (32, 3, 6));
+    }
+
+    #[test]
+    fn leaving_zero_does_not_count_it() {
+        let mut dial = ReferenceDial::new(100, 0).unwrap();
+
+        dial.turn(&get_dial_rotation("L5"));
+        dial.turn(&get_dial_rotation("R0"));
+
+        assert_eq!((dial.position(), dial.zero_landings(), dial.zero_clicks()), (95, 0, 0));
+    }
+}
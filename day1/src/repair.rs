@@ -0,0 +1,177 @@
+use crate::{CountingRule, Dial, DialMovement, DialRotation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationEdit {
+    FlipDirection,
+    ChangeAmount(i32),
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotationRepair {
+    pub index: usize,
+    pub edit: RotationEdit,
+    /// The rotation that replaces the original one, or `None` when it is deleted.
+    pub replacement: Option<DialRotation>,
+}
+
+/// Finds every single edit to `rotations` that makes the run starting from `dial` count exactly
+/// `target_count` under `rule`. Amount changes go up to `max_amount_change` either way and never
+/// make an amount negative. A negative `max_amount_change` is an error.
+///
+/// Each candidate is scored as prefix count + edited rotation + suffix count from where the edited
+/// rotation ends. Suffix counts for every possible position are kept for one instruction at a
+/// time, so the search is O(rotations * (dial size + edits)) instead of a re-simulation per edit.
+pub fn find_single_edit_repairs(
+    dial: &Dial,
+    rotations: &[DialRotation],
+    rule: CountingRule,
+    target_count: u64,
+    max_amount_change: i32,
+) -> Result<Vec<RotationRepair>, String> {
+    if max_amount_change < 0 {
+        return Err(format!("Invalid maximum amount change {}", max_amount_change));
+    }
+    let size = dial.get_size();
+
+    let mut prefix_positions = Vec::with_capacity(rotations.len());
+    let mut prefix_counts = Vec::with_capacity(rotations.len());
+    let mut forward_dial = dial.clone();
+    let mut count = 0;
+    for rotation in rotations {
+        prefix_positions.push(forward_dial.get_current_position());
+        prefix_counts.push(count);
+        count += rule.count_movement(&forward_dial.rotate(rotation));
+    }
+
+    let mut suffix_counts = vec![0u64; size as usize];
+    let mut repairs = Vec::new();
+
+    for (index, rotation) in rotations.iter().enumerate().rev() {
+        let start = prefix_positions[index];
+        let prefix_count = prefix_counts[index];
+        let mut consider = |edit: RotationEdit, replacement: Option<DialRotation>| {
+            let total = match replacement {
+                Some(replacement) => {
+                    let movement = DialMovement::new(size, start, replacement);
+                    prefix_count + rule.count_movement(&movement) + suffix_counts[movement.end as usize]
+                }
+                None => prefix_count + suffix_counts[start as usize],
+            };
+            if total == target_count {
+                repairs.push(RotationRepair { index, edit, replacement });
+            }
+        };
+
+        if rotation.get_value() != 0 {
+            let flipped = DialRotation::new(rotation.get_direction().opposite(), rotation.get_value());
+            consider(RotationEdit::FlipDirection, Some(flipped));
+        }
+        for change in (-max_amount_change..=max_amount_change).filter(|&change| change != 0) {
+            if let Some(amount) = rotation.get_value().checked_add(change).filter(|&amount| amount >= 0) {
+                consider(RotationEdit::ChangeAmount(change), Some(DialRotation::new(rotation.get_direction(), amount)));
+            }
+        }
+        consider(RotationEdit::Delete, None);
+
+        suffix_counts = (0..size)
+            .map(|position| {
+                let movement = DialMovement::new(size, position, *rotation);
+                rule.count_movement(&movement) + suffix_counts[movement.end as usize]
+            })
+            .collect();
+    }
+
+    repairs.sort_by_key(|repair| repair.index);
+    Ok(repairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_dial_rotation, test_rotations, Direction};
+
+    fn count_by_simulation(rotations: &[DialRotation], rule: CountingRule) -> u64 {
+        let mut dial = Dial::new(50, 0).unwrap();
+        rotations.iter().map(|rotation| rule.count_movement(&dial.rotate(rotation))).sum()
+    }
+
+    #[test]
+    fn every_repair_fixes_the_count_when_re_simulated() {
+        let rotations = test_rotations();
+
+        for (rule, target_count) in [(CountingRule::LandingsOnZero, 2), (CountingRule::ClicksThroughZero, 5)] {
+            let repairs = find_single_edit_repairs(&Dial::new(50, 0).unwrap(), &rotations, rule, target_count, 3).unwrap();
+
+            assert!(!repairs.is_empty());
+            for repair in repairs {
+                let mut edited = rotations.clone();
+                match repair.replacement {
+                    Some(replacement) => edited[repair.index] = replacement,
+                    None => { edited.remove(repair.index); },
+                }
+                assert_eq!(count_by_simulation(&edited, rule), target_count, "{:?}", repair);
+            }
+        }
+    }
+
+    #[test]
+    fn repairs_match_brute_force_search() {
+        let rotations = test_rotations();
+        let rule = CountingRule::ClicksThroughZero;
+
+        let repairs = find_single_edit_repairs(&Dial::new(50, 0).unwrap(), &rotations, rule, 7, 2).unwrap();
+
+        let mut expected = Vec::new();
+        for (index, rotation) in rotations.iter().enumerate() {
+            let mut candidates = vec![(RotationEdit::FlipDirection, Some(DialRotation::new(rotation.get_direction().opposite(), rotation.get_value())))];
+            for change in [-2, -1, 1, 2] {
+                candidates.push((RotationEdit::ChangeAmount(change), Some(DialRotation::new(rotation.get_direction(), rotation.get_value() + change))));
+            }
+            candidates.push((RotationEdit::Delete, None));
+
+            for (edit, replacement) in candidates {
+                let mut edited = rotations.clone();
+                match replacement {
+                    Some(replacement) => edited[index] = replacement,
+                    None => { edited.remove(index); },
+                }
+                if count_by_simulation(&edited, rule) == 7 {
+                    expected.push(RotationRepair { index, edit, replacement });
+                }
+            }
+        }
+        assert_eq!(repairs, expected);
+    }
+
+    #[test]
+    fn flipping_a_single_rotation_can_land_on_zero() {
+        let rotations = vec![get_dial_rotation("L50"), get_dial_rotation("L10")];
+
+        let repairs = find_single_edit_repairs(&Dial::new(60, 0).unwrap(), &rotations, CountingRule::LandingsOnZero, 1, 0).unwrap();
+
+        assert_eq!(repairs, vec![
+            RotationRepair { index: 0, edit: RotationEdit::FlipDirection, replacement: Some(DialRotation::new(Direction::Right, 50)) },
+        ]);
+    }
+
+    #[test]
+    fn amount_changes_never_go_negative() {
+        let rotations = vec![get_dial_rotation("R1")];
+
+        let repairs = find_single_edit_repairs(&Dial::new(0, 0).unwrap(), &rotations, CountingRule::LandingsOnZero, 1, 5).unwrap();
+
+        assert!(repairs.iter().all(|repair| repair.replacement.is_none_or(|r| r.get_value() >= 0)));
+        assert!(repairs.contains(&RotationRepair { index: 0, edit: RotationEdit::ChangeAmount(-1), replacement: Some(DialRotation::new(Direction::Right, 0)) }));
+    }
+
+    #[test]
+    fn negative_amount_change_limit_is_an_error() {
+        let rotations = vec![get_dial_rotation("R1")];
+
+        for max_amount_change in [-1, i32::MIN] {
+            let result = find_single_edit_repairs(&Dial::new(0, 0).unwrap(), &rotations, CountingRule::LandingsOnZero, 1, max_amount_change);
+            assert!(result.is_err());
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{load_dial_rotations, visit_histogram, CountingRule, Dial, DialRotation, Direction, UndoableDial, DEFAULT_DIAL_SIZE};
+use std::io::{self, BufRead, Write};
+
+const CHECKPOINT_INTERVAL: usize = 64;
+const RENDER_WIDTH: i32 = 50;
+
+const HELP: &str = "\
+Rotations:     L68, R300, or several on one line (L50 R1)
+reset          go back to the start position and forget the history
+start <pos>    start again from another position
+load <path>    apply every rotation in a file
+undo [n]       take back the last n rotations (default 1)
+stats          show totals for the rotations so far
+help           show this message
+quit           leave";
+
+/// The state behind `day1 repl`: one dial per counting rule, driven by the same rotations.
+pub struct ReplSession {
+    size: i32,
+    start: i32,
+    dials: Vec<UndoableDial>,
+}
+
+impl ReplSession {
+    pub fn new(size: i32, start: i32) -> Result<ReplSession, String> {
+        let dials = CountingRule::ALL
+            .iter()
+            .map(|&rule| UndoableDial::new(Dial::with_size(size, start, 0)?, rule, CHECKPOINT_INTERVAL))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(ReplSession { size, start, dials })
+    }
+
+    pub fn position(&self) -> i32 {
+        self.dials[0].dial().get_current_position()
+    }
+
+    pub fn zero_count(&self, rule: CountingRule) -> i32 {
+        self.dials.iter().find(|dial| dial.rule() == rule).unwrap().dial().get_zero_count()
+    }
+
+    fn zero_counts(&self) -> [i32; 2] {
+        CountingRule::ALL.map(|rule| self.zero_count(rule))
+    }
+
+    pub fn history(&self) -> &[DialRotation] {
+        &self.dials[0].history()[..self.dials[0].step()]
+    }
+
+    /// Handles one line of input, returning what to print, or `None` when the session should end.
+    pub fn handle_line(&mut self, line: &str) -> Option<String> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Some(String::new());
+        };
+        let argument = words.next();
+
+        let output = match command {
+            "quit" | "exit" => return None,
+            "help" => HELP.to_string(),
+            "reset" => self.restart(self.start),
+            "start" => match argument.map(str::parse::<i32>) {
+                Some(Ok(position)) => self.restart(position),
+                _ => "Usage: start <position>".to_string(),
+            },
+            "load" => match argument {
+                Some(path) => self.load(path),
+                None => "Usage: load <path>".to_string(),
+            },
+            "undo" => match argument.map(str::parse::<usize>).unwrap_or(Ok(1)) {
+                Ok(count) => self.undo(count),
+                Err(_) => "Usage: undo [count]".to_string(),
+            },
+            "stats" => self.stats(),
+            _ => self.rotate_all(line),
+        };
+        Some(output)
+    }
+
+    fn restart(&mut self, start: i32) -> String {
+        match ReplSession::new(self.size, start) {
+            Ok(session) => {
+                *self = session;
+                self.status()
+            },
+            Err(error) => error,
+        }
+    }
+
+    fn rotate_all(&mut self, line: &str) -> String {
+        let rotations = match line.split_whitespace().map(str::parse::<DialRotation>).collect::<Result<Vec<_>, _>>() {
+            Ok(rotations) => rotations,
+            Err(error) => return format!("{} (type help for commands)", error),
+        };
+
+        let mut output = String::new();
+        for rotation in &rotations {
+            let before = self.position();
+            let [landings_before, clicks_before] = self.zero_counts();
+            if let Err(error) = self.apply(rotation) {
+                output.push_str(&format!("{}: {}\n", rotation, error));
+                break;
+            }
+            let [landings, clicks] = self.zero_counts();
+
+            output.push_str(&format!(
+                "{}: {} -> {}   landings +{}   clicks +{}\n",
+                rotation,
+                before,
+                self.position(),
+                landings - landings_before,
+                clicks - clicks_before,
+            ));
+        }
+        output.push_str(&self.status());
+        output
+    }
+
+    fn load(&mut self, path: &str) -> String {
+        match load_dial_rotations(path) {
+            Ok(rotations) => {
+                for (index, rotation) in rotations.iter().enumerate() {
+                    if let Err(error) = self.apply(rotation) {
+                        return format!("Stopped after {} of {} rotations from {}: {}\n{}", index, rotations.len(), path, error, self.status());
+                    }
+                }
+                format!("Loaded {} rotations from {}\n{}", rotations.len(), path, self.status())
+            },
+            Err(error) => error,
+        }
+    }
+
+    /// Turns every dial, or none of them if one of the counts would overflow.
+    fn apply(&mut self, rotation: &DialRotation) -> Result<(), String> {
+        for applied in 0..self.dials.len() {
+            if let Err(error) = self.dials[applied].apply(rotation) {
+                for dial in &mut self.dials[..applied] {
+                    dial.undo()?;
+                }
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    fn undo(&mut self, count: usize) -> String {
+        let mut undone = 0;
+        while undone < count && self.dials[0].can_undo() {
+            for dial in &mut self.dials {
+                if let Err(error) = dial.undo() {
+                    return format!("{}\n{}", error, self.status());
+                }
+            }
+            undone += 1;
+        }
+        format!("Undid {} rotation{}\n{}", undone, if undone == 1 { "" } else { "s" }, self.status())
+    }
+
+    fn stats(&self) -> String {
+        let history = self.history();
+        let lefts = history.iter().filter(|r| r.get_direction() == Direction::Left).count();
+        let histogram = visit_histogram(&Dial::with_size(self.size, self.start, 0).unwrap(), history);
+        let (most_visited, visits) = histogram.most_visited();
+
+        format!(
+            "Rotations: {} ({} left, {} right) from start {}\nClicks turned: {}\nMost visited: {} ({} clicks)\n{}",
+            history.len(),
+            lefts,
+            history.len() - lefts,
+            self.start,
+            histogram.total_clicks(),
+            most_visited,
+            visits,
+            self.status(),
+        )
+    }
+
+    fn status(&self) -> String {
+        format!(
+            "Position {}   landings on 0: {}   clicks through 0: {}\n{}",
+            self.position(),
+            self.zero_count(CountingRule::LandingsOnZero),
+            self.zero_count(CountingRule::ClicksThroughZero),
+            render_dial(self.dials[0].dial()),
+        )
+    }
+}
+
+impl Default for ReplSession {
+    fn default() -> ReplSession {
+        ReplSession::new(DEFAULT_DIAL_SIZE, 50).unwrap()
+    }
+}
+
+/// Draws the dial unrolled into a strip, with `^` under the current position and the 0 mark at
+/// the left edge. Large dials share one cell between several positions.
+pub fn render_dial(dial: &Dial) -> String {
+    let size = dial.get_size();
+    let width = size.min(RENDER_WIDTH);
+    let cell = |position: i32| (position as i64 * width as i64 / size as i64) as usize;
+
+    let mut strip = vec!['-'; width as usize];
+    for quarter in 1..4 {
+        strip[cell(size * quarter / 4)] = '+';
+    }
+    strip[0] = '0';
+
+    let mut pointer = vec![' '; width as usize];
+    pointer[cell(dial.get_current_position())] = '^';
+
+    format!("[{}]\n {}", strip.iter().collect::<String>(), pointer.iter().collect::<String>().trim_end())
+}
+
+/// Reads lines from `input` until it ends or the user quits, writing a prompt before each one.
+pub fn run_repl<R: BufRead, W: Write>(session: &mut ReplSession, input: R, mut output: W) -> io::Result<()> {
+    writeln!(output, "Dial REPL, type help for commands")?;
+    writeln!(output, "{}", session.status())?;
+    write!(output, "> ")?;
+    output.flush()?;
+
+    for line in input.lines() {
+        match session.handle_line(&line?) {
+            Some(text) if text.is_empty() => {},
+            Some(text) => writeln!(output, "{}", text.trim_end())?,
+            None => return Ok(()),
+        }
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+    writeln!(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotations_report_both_rules() {
+        let mut session = ReplSession::new(100, 99).unwrap();
+
+        let output = session.handle_line("L50").unwrap();
+
+        assert!(output.starts_with("L50: 99 -> 49   landings +0   clicks +0\n"), "{}", output);
+        let output = session.handle_line("R51 L300").unwrap();
+        assert!(output.contains("R51: 49 -> 0   landings +1   clicks +1\n"), "{}", output);
+        assert!(output.contains("L300: 0 -> 0   landings +1   clicks +3\n"), "{}", output);
+        assert_eq!(session.zero_count(CountingRule::ClicksThroughZero), 4);
+    }
+
+    #[test]
+    fn test_input_gives_both_answers() {
+        let mut session = ReplSession::default();
+
+        session.handle_line("L68 L30 R48 L5 R60 L55 L1 L99 R14 L82");
+
+        assert_eq!(session.position(), 32);
+        assert_eq!(session.zero_count(CountingRule::LandingsOnZero), 3);
+        assert_eq!(session.zero_count(CountingRule::ClicksThroughZero), 6);
+    }
+
+    #[test]
+    fn undo_reset_and_start_commands() {
+        let mut session = ReplSession::default();
+        session.handle_line("R50 R100 L1");
+
+        assert!(session.handle_line("undo 2").unwrap().starts_with("Undid 2 rotations"));
+        assert_eq!((session.position(), session.zero_count(CountingRule::ClicksThroughZero)), (0, 1));
+        assert!(session.handle_line("undo 5").unwrap().starts_with("Undid 1 rotation\n"));
+
+        session.handle_line("R7");
+        session.handle_line("reset");
+        assert_eq!((session.position(), session.history().len()), (50, 0));
+
+        session.handle_line("start 99");
+        assert_eq!(session.position(), 99);
+        assert!(session.handle_line("start 100").unwrap().contains("Invalid dial starting position"));
+        assert_eq!(session.position(), 99);
+    }
+
+    #[test]
+    fn stats_summarise_the_history() {
+        let mut session = ReplSession::default();
+        session.handle_line("L10 R250");
+
+        let stats = session.handle_line("stats").unwrap();
+
+        assert!(stats.starts_with("Rotations: 2 (1 left, 1 right) from start 50\nClicks turned: 260\n"), "{}", stats);
+    }
+
+    #[test]
+    fn bad_input_and_missing_files_are_reported() {
+        let mut session = ReplSession::default();
+
+        assert!(session.handle_line("X12").unwrap().contains("type help"));
+        assert!(session.handle_line("load input/does_not_exist.txt").unwrap().starts_with("input/does_not_exist.txt"));
+        assert_eq!(session.history().len(), 0);
+    }
+
+    #[test]
+    fn dial_rendering_points_at_the_position() {
+        let dial = Dial::new(50, 0).unwrap();
+
+        let rendering = render_dial(&dial);
+
+        assert_eq!(rendering, format!("[0-----------+------------+-----------+------------]\n {}^", " ".repeat(25)));
+    }
+
+    #[test]
+    fn repl_runs_until_quit() {
+        let mut session = ReplSession::default();
+        let mut output = Vec::new();
+
+        run_repl(&mut session, "R50\n\nquit\nR1\n".as_bytes(), &mut output).unwrap();
+
+        assert_eq!(session.position(), 0);
+        assert!(String::from_utf8(output).unwrap().contains("R50: 50 -> 0"));
+    }
+}
@@ -0,0 +1,377 @@
+use crate::{CountingRule, Dial, DialRotation, Direction};
+use std::fmt;
+
+pub const SNAPSHOT_VERSION: u16 = 1;
+
+const BINARY_MAGIC: &[u8; 4] = b"DIAL";
+const TEXT_MAGIC: &str = "DIAL";
+const BINARY_LEN: usize = 4 + 2 + 1 + 4 + 4 + 8 + 8 + 8 + 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    Malformed(String),
+    UnsupportedVersion(u16),
+    ChecksumMismatch,
+    /// The snapshot is intact but does not fit the rotations it is being resumed against.
+    Stale(String),
+    Invalid(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Malformed(reason) => write!(f, "malformed snapshot: {}", reason),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {} (expected {})", version, SNAPSHOT_VERSION)
+            },
+            SnapshotError::ChecksumMismatch => write!(f, "snapshot checksum does not match its contents"),
+            SnapshotError::Stale(reason) => write!(f, "stale snapshot: {}", reason),
+            SnapshotError::Invalid(reason) => write!(f, "invalid snapshot: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// Everything needed to carry on a run: the dial, the rule it was counting with, how many
+/// rotations it has been through and a fingerprint of those rotations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DialSnapshot {
+    pub rule: CountingRule,
+    pub size: i32,
+    pub position: i32,
+    pub zero_count: i64,
+    pub instruction_index: u64,
+    pub input_fingerprint: u64,
+}
+
+impl DialSnapshot {
+    /// `DIAL v1 rule=clicks size=100 position=32 zero_count=6 index=10 input=<hex> checksum=<hex>`
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} v{} rule={} size={} position={} zero_count={} index={} input={:016x} checksum={:016x}",
+            TEXT_MAGIC,
+            SNAPSHOT_VERSION,
+            self.rule,
+            self.size,
+            self.position,
+            self.zero_count,
+            self.instruction_index,
+            self.input_fingerprint,
+            self.checksum(),
+        )
+    }
+
+    pub fn from_text(text: &str) -> Result<DialSnapshot, SnapshotError> {
+        let malformed = |reason: &str| SnapshotError::Malformed(reason.to_string());
+        let mut fields = text.split_whitespace();
+
+        if fields.next() != Some(TEXT_MAGIC) {
+            return Err(malformed("missing DIAL header"));
+        }
+        let version = fields
+            .next()
+            .and_then(|field| field.strip_prefix('v'))
+            .and_then(|version| version.parse::<u16>().ok())
+            .ok_or_else(|| malformed("missing version"))?;
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let mut field = |name: &str| {
+            fields
+                .next()
+                .and_then(|field| field.strip_prefix(name))
+                .and_then(|field| field.strip_prefix('='))
+                .ok_or_else(|| SnapshotError::Malformed(format!("missing {} field", name)))
+        };
+        let number = |value: &str, name: &str| SnapshotError::Malformed(format!("bad {} value {:?}", name, value));
+
+        let rule = field("rule")?;
+        let rule = rule.parse::<CountingRule>().map_err(SnapshotError::Malformed)?;
+        let size = field("size")?;
+        let size = size.parse().map_err(|_| number(size, "size"))?;
+        let position = field("position")?;
+        let position = position.parse().map_err(|_| number(position, "position"))?;
+        let zero_count = field("zero_count")?;
+        let zero_count = zero_count.parse().map_err(|_| number(zero_count, "zero_count"))?;
+        let index = field("index")?;
+        let instruction_index = index.parse().map_err(|_| number(index, "index"))?;
+        let input = field("input")?;
+        let input_fingerprint = u64::from_str_radix(input, 16).map_err(|_| number(input, "input"))?;
+        let checksum = field("checksum")?;
+        let checksum = u64::from_str_radix(checksum, 16).map_err(|_| number(checksum, "checksum"))?;
+        if fields.next().is_some() {
+            return Err(malformed("unexpected trailing fields"));
+        }
+
+        DialSnapshot { rule, size, position, zero_count, instruction_index, input_fingerprint }.verified(checksum)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.payload();
+        bytes.extend_from_slice(&self.checksum().to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<DialSnapshot, SnapshotError> {
+        if bytes.len() < 6 || &bytes[..4] != BINARY_MAGIC {
+            return Err(SnapshotError::Malformed("missing DIAL header".to_string()));
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        if bytes.len() != BINARY_LEN {
+            return Err(SnapshotError::Malformed(format!("expected {} bytes, got {}", BINARY_LEN, bytes.len())));
+        }
+
+        let rule = match bytes[6] {
+            0 => CountingRule::LandingsOnZero,
+            1 => CountingRule::ClicksThroughZero,
+            other => return Err(SnapshotError::Malformed(format!("unknown counting rule {}", other))),
+        };
+        let array = |at: usize| -> [u8; 8] { bytes[at..at + 8].try_into().unwrap() };
+        let short = |at: usize| -> [u8; 4] { bytes[at..at + 4].try_into().unwrap() };
+
+        DialSnapshot {
+            rule,
+            size: i32::from_le_bytes(short(7)),
+            position: i32::from_le_bytes(short(11)),
+            zero_count: i64::from_le_bytes(array(15)),
+            instruction_index: u64::from_le_bytes(array(23)),
+            input_fingerprint: u64::from_le_bytes(array(31)),
+        }
+        .verified(u64::from_le_bytes(array(39)))
+    }
+
+    /// The fields in binary order, without the checksum. Both formats checksum these bytes.
+    fn payload(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(BINARY_LEN);
+        bytes.extend_from_slice(BINARY_MAGIC);
+        bytes.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        bytes.push(match self.rule {
+            CountingRule::LandingsOnZero => 0,
+            CountingRule::ClicksThroughZero => 1,
+        });
+        bytes.extend_from_slice(&self.size.to_le_bytes());
+        bytes.extend_from_slice(&self.position.to_le_bytes());
+        bytes.extend_from_slice(&self.zero_count.to_le_bytes());
+        bytes.extend_from_slice(&self.instruction_index.to_le_bytes());
+        bytes.extend_from_slice(&self.input_fingerprint.to_le_bytes());
+        bytes
+    }
+
+    fn checksum(&self) -> u64 {
+        fnv1a(FNV_OFFSET_BASIS, &self.payload())
+    }
+
+    fn verified(self, checksum: u64) -> Result<DialSnapshot, SnapshotError> {
+        if checksum != self.checksum() {
+            return Err(SnapshotError::ChecksumMismatch);
+        }
+        Ok(self)
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// A fingerprint of a run of rotations, so a snapshot can tell if it is resumed on other input.
+pub fn rotations_fingerprint(rotations: &[DialRotation]) -> u64 {
+    extend_fingerprint(FNV_OFFSET_BASIS, rotations)
+}
+
+fn extend_fingerprint(hash: u64, rotations: &[DialRotation]) -> u64 {
+    rotations.iter().fold(hash, |hash, rotation| {
+        let direction = match rotation.get_direction() {
+            Direction::Left => b'L',
+            Direction::Right => b'R',
+        };
+        fnv1a(fnv1a(hash, &[direction]), &rotation.get_value().to_le_bytes())
+    })
+}
+
+/// Turns a dial through a rotation list under one counting rule, keeping track of how far it
+/// has got so the run can be snapshotted and resumed.
+pub struct RotationRunner {
+    dial: Dial,
+    rule: CountingRule,
+    instruction_index: usize,
+    input_fingerprint: u64,
+}
+
+impl RotationRunner {
+    pub fn new(dial: Dial, rule: CountingRule) -> RotationRunner {
+        RotationRunner { dial, rule, instruction_index: 0, input_fingerprint: rotations_fingerprint(&[]) }
+    }
+
+    /// Picks up a run from `snapshot`, checking that it was taken over the same rotations.
+    pub fn resume(snapshot: &DialSnapshot, rotations: &[DialRotation]) -> Result<RotationRunner, SnapshotError> {
+        let zero_count = i32::try_from(snapshot.zero_count)
+            .map_err(|_| SnapshotError::Invalid(format!("zero count {} is out of range", snapshot.zero_count)))?;
+        let dial = Dial::with_size(snapshot.size, snapshot.position, zero_count).map_err(SnapshotError::Invalid)?;
+
+        let instruction_index = usize::try_from(snapshot.instruction_index)
+            .ok()
+            .filter(|&index| index <= rotations.len())
+            .ok_or_else(|| {
+                SnapshotError::Stale(format!("snapshot is at rotation {} but there are only {}", snapshot.instruction_index, rotations.len()))
+            })?;
+        if rotations_fingerprint(&rotations[..instruction_index]) != snapshot.input_fingerprint {
+            return Err(SnapshotError::Stale("rotations before the snapshot point have changed".to_string()));
+        }
+
+        Ok(RotationRunner { dial, rule: snapshot.rule, instruction_index, input_fingerprint: snapshot.input_fingerprint })
+    }
+
+    pub fn dial(&self) -> &Dial {
+        &self.dial
+    }
+
+    pub fn rule(&self) -> CountingRule {
+        self.rule
+    }
+
+    pub fn instruction_index(&self) -> usize {
+        self.instruction_index
+    }
+
+    /// Runs up to `count` more rotations, returning how many were actually run. Stops at a
+    /// rotation that would overflow the zero count, so the runner can still be snapshotted there.
+    pub fn run_for(&mut self, rotations: &[DialRotation], count: usize) -> Result<usize, String> {
+        let end = rotations.len().min(self.instruction_index.saturating_add(count));
+        let pending = &rotations[self.instruction_index.min(end)..end];
+
+        for (run, rotation) in pending.iter().enumerate() {
+            let turned = match self.rule {
+                CountingRule::LandingsOnZero => self.dial.turn_dial_in_direction_part1(rotation),
+                CountingRule::ClicksThroughZero => self.dial.turn_dial_in_direction_part2(rotation),
+            };
+            if let Err(error) = turned {
+                self.input_fingerprint = extend_fingerprint(self.input_fingerprint, &pending[..run]);
+                self.instruction_index += run;
+                return Err(format!("rotation {}: {}", self.instruction_index, error));
+            }
+        }
+        self.input_fingerprint = extend_fingerprint(self.input_fingerprint, pending);
+        self.instruction_index += pending.len();
+        Ok(pending.len())
+    }
+
+    pub fn run(&mut self, rotations: &[DialRotation]) -> Result<(), String> {
+        self.run_for(rotations, usize::MAX).map(drop)
+    }
+
+    pub fn snapshot(&self) -> DialSnapshot {
+        DialSnapshot {
+            rule: self.rule,
+            size: self.dial.get_size(),
+            position: self.dial.get_current_position(),
+            zero_count: self.dial.get_zero_count() as i64,
+            instruction_index: self.instruction_index as u64,
+            input_fingerprint: self.input_fingerprint,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_dial_rotation, test_rotations};
+
+    fn halfway_snapshot(rule: CountingRule) -> DialSnapshot {
+        let mut runner = RotationRunner::new(Dial::new(50, 0).unwrap(), rule);
+        runner.run_for(&test_rotations(), 5).unwrap();
+        runner.snapshot()
+    }
+
+    #[test]
+    fn resuming_from_text_and_binary_snapshots_gives_the_same_answers() {
+        let rotations = test_rotations();
+
+        for (rule, expected) in [(CountingRule::LandingsOnZero, 3), (CountingRule::ClicksThroughZero, 6)] {
+            let snapshot = halfway_snapshot(rule);
+
+            for restored in [DialSnapshot::from_text(&snapshot.to_text()).unwrap(), DialSnapshot::from_bytes(&snapshot.to_bytes()).unwrap()] {
+                assert_eq!(restored, snapshot);
+                let mut runner = RotationRunner::resume(&restored, &rotations).unwrap();
+                runner.run(&rotations).unwrap();
+                assert_eq!(runner.dial().get_zero_count(), expected);
+                assert_eq!(runner.instruction_index(), 10);
+            }
+        }
+    }
+
+    #[test]
+    fn resumed_snapshot_matches_uninterrupted_snapshot() {
+        let rotations = test_rotations();
+        let mut uninterrupted = RotationRunner::new(Dial::new(50, 0).unwrap(), CountingRule::ClicksThroughZero);
+        uninterrupted.run(&rotations).unwrap();
+
+        let mut resumed = RotationRunner::resume(&halfway_snapshot(CountingRule::ClicksThroughZero), &rotations).unwrap();
+        resumed.run(&rotations).unwrap();
+
+        assert_eq!(resumed.snapshot(), uninterrupted.snapshot());
+    }
+
+    #[test]
+    fn text_snapshot_is_one_readable_line() {
+        let text = halfway_snapshot(CountingRule::ClicksThroughZero).to_text();
+
+        assert!(text.starts_with("DIAL v1 rule=clicks size=100 position=55 zero_count=3 index=5 input="), "{}", text);
+        assert!(!text.contains('\n'));
+    }
+
+    #[test]
+    fn corrupted_snapshots_fail_the_checksum() {
+        let snapshot = halfway_snapshot(CountingRule::LandingsOnZero);
+
+        let text = snapshot.to_text().replace("position=55", "position=56");
+        assert_eq!(DialSnapshot::from_text(&text), Err(SnapshotError::ChecksumMismatch));
+
+        let mut bytes = snapshot.to_bytes();
+        bytes[12] ^= 0x40;
+        assert_eq!(DialSnapshot::from_bytes(&bytes), Err(SnapshotError::ChecksumMismatch));
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let snapshot = halfway_snapshot(CountingRule::LandingsOnZero);
+
+        let text = snapshot.to_text().replacen("v1", "v0", 1);
+        assert_eq!(DialSnapshot::from_text(&text), Err(SnapshotError::UnsupportedVersion(0)));
+
+        let mut bytes = snapshot.to_bytes();
+        bytes[4] = 2;
+        assert_eq!(DialSnapshot::from_bytes(&bytes), Err(SnapshotError::UnsupportedVersion(2)));
+    }
+
+    #[test]
+    fn truncated_or_garbled_snapshots_are_malformed() {
+        let snapshot = halfway_snapshot(CountingRule::LandingsOnZero);
+
+        assert!(matches!(DialSnapshot::from_bytes(&snapshot.to_bytes()[..20]), Err(SnapshotError::Malformed(_))));
+        assert!(matches!(DialSnapshot::from_text("DIAL v1 rule=clicks size=100"), Err(SnapshotError::Malformed(_))));
+        assert!(matches!(DialSnapshot::from_text("hello"), Err(SnapshotError::Malformed(_))));
+    }
+
+    #[test]
+    fn snapshots_of_other_input_are_stale() {
+        let snapshot = halfway_snapshot(CountingRule::LandingsOnZero);
+        let mut rotations = test_rotations();
+
+        assert!(matches!(RotationRunner::resume(&snapshot, &rotations[..3]), Err(SnapshotError::Stale(_))));
+
+        rotations[1] = get_dial_rotation("L31");
+        assert!(matches!(RotationRunner::resume(&snapshot, &rotations), Err(SnapshotError::Stale(_))));
+
+        rotations[1] = get_dial_rotation("L30");
+        rotations[7] = get_dial_rotation("R1");
+        assert!(RotationRunner::resume(&snapshot, &rotations).is_ok());
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{DialRotation, Direction};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectionConstraint {
+    /// Each target is reached by whichever way round is shorter, turning right on a tie.
+    Free,
+    /// Targets are reached by turning alternately left and right, as on a combination lock.
+    /// With no `first` direction, both are tried and the shorter program wins.
+    Alternating { first: Option<Direction> },
+}
+
+/// Builds the fewest-clicks rotation program that takes a dial of `size` positions from `start`
+/// to each target in turn, finishing each rotation on its target.
+///
+/// Without a direction constraint a target the dial is already on needs no rotation. When
+/// directions alternate every target gets exactly one rotation, which may be a zero-click one.
+pub fn shortest_combination_program(
+    size: i32,
+    start: i32,
+    targets: &[i32],
+    constraint: DirectionConstraint,
+) -> Result<Vec<DialRotation>, String> {
+    if size < 1 {
+        return Err("Invalid dial size".to_string());
+    }
+    if let Some(position) = std::iter::once(&start).chain(targets).find(|p| !(0..size).contains(*p)) {
+        return Err(format!("Invalid position {} for a dial of size {}", position, size));
+    }
+
+    let distance = |from: i32, to: i32, direction: Direction| match direction {
+        Direction::Right => (to - from).rem_euclid(size),
+        Direction::Left => (from - to).rem_euclid(size),
+    };
+
+    match constraint {
+        DirectionConstraint::Free => {
+            let mut position = start;
+            let mut program = Vec::new();
+            for &target in targets {
+                if target != position {
+                    let (right, left) = (distance(position, target, Direction::Right), distance(position, target, Direction::Left));
+                    program.push(if right <= left {
+                        DialRotation::new(Direction::Right, right)
+                    } else {
+                        DialRotation::new(Direction::Left, left)
+                    });
+                }
+                position = target;
+            }
+            Ok(program)
+        },
+        DirectionConstraint::Alternating { first } => {
+            let alternating_from = |first: Direction| {
+                let mut position = start;
+                let mut direction = first;
+                targets
+                    .iter()
+                    .map(|&target| {
+                        let rotation = DialRotation::new(direction, distance(position, target, direction));
+                        position = target;
+                        direction = direction.opposite();
+                        rotation
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            Ok(match first {
+                Some(first) => alternating_from(first),
+                None => {
+                    let clicks = |program: &Vec<DialRotation>| program.iter().map(|r| r.get_value() as i64).sum::<i64>();
+                    let (left_first, right_first) = (alternating_from(Direction::Left), alternating_from(Direction::Right));
+                    if clicks(&right_first) <= clicks(&left_first) { right_first } else { left_first }
+                }
+            })
+        },
+    }
+}
+
+/// Writes rotations one per line in the same `L68` format the input files use.
+pub fn format_rotation_program(rotations: &[DialRotation]) -> String {
+    rotations.iter().map(|rotation| format!("{}\n", rotation)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_dial_rotations, Dial, ParseMode};
+
+    fn landing_positions(size: i32, start: i32, program: &[DialRotation]) -> Vec<i32> {
+        let mut dial = Dial::with_size(size, start, 0).unwrap();
+        program.iter().map(|rotation| dial.rotate(rotation).end).collect()
+    }
+
+    #[test]
+    fn free_program_takes_the_shorter_way_round() {
+        let program = shortest_combination_program(100, 50, &[45, 95, 10], DirectionConstraint::Free).unwrap();
+
+        assert_eq!(format_rotation_program(&program), "L5\nR50\nR15\n");
+    }
+
+    #[test]
+    fn free_program_skips_targets_already_reached() {
+        let program = shortest_combination_program(100, 50, &[50, 50, 0], DirectionConstraint::Free).unwrap();
+
+        assert_eq!(landing_positions(100, 50, &program), vec![0]);
+    }
+
+    #[test]
+    fn alternating_program_switches_direction_every_target() {
+        let program = shortest_combination_program(40, 0, &[10, 30, 20], DirectionConstraint::Alternating { first: Some(Direction::Right) }).unwrap();
+
+        assert_eq!(format_rotation_program(&program), "R10\nL20\nR30\n");
+        assert_eq!(landing_positions(40, 0, &program), vec![10, 30, 20]);
+    }
+
+    #[test]
+    fn alternating_program_picks_the_cheaper_first_direction() {
+        let program = shortest_combination_program(40, 0, &[35, 25], DirectionConstraint::Alternating { first: None }).unwrap();
+
+        assert_eq!(format_rotation_program(&program), "L5\nR30\n");
+    }
+
+    #[test]
+    fn program_round_trips_through_the_rotation_parser() {
+        let targets = [17, 83, 2, 2, 99, 50];
+
+        for constraint in [DirectionConstraint::Free, DirectionConstraint::Alternating { first: None }] {
+            let program = shortest_combination_program(100, 50, &targets, constraint).unwrap();
+            let text = format_rotation_program(&program);
+
+            let parsed = parse_dial_rotations(text.as_bytes(), ParseMode::Strict).unwrap();
+
+            assert_eq!(parsed.rotations, program);
+            let mut landings = landing_positions(100, 50, &parsed.rotations);
+            landings.dedup();
+            assert_eq!(landings, vec![17, 83, 2, 99, 50]);
+        }
+    }
+
+    #[test]
+    fn targets_off_the_dial_are_errors() {
+        assert!(shortest_combination_program(12, 0, &[3, 12], DirectionConstraint::Free).is_err());
+        assert!(shortest_combination_program(12, -1, &[3], DirectionConstraint::Free).is_err());
+    }
+}
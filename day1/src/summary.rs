@@ -0,0 +1,290 @@
+use crate::{visit_histogram, CountingRule, Dial, DialMovement, DialRotation};
+use std::ops::Range;
+
+/// The effect of a run of rotations on a dial of `size` positions, for every possible start:
+/// where the dial ends up and how many zeros each counting rule sees on the way.
+///
+/// Summaries compose associatively with `then`, and `identity` is the empty run, so any split of
+/// a rotation list can be summarised piece by piece and combined in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationSummary {
+    size: i32,
+    offset: i32,
+    zero_landings: Vec<u64>,
+    zero_clicks: Vec<u64>,
+}
+
+impl RotationSummary {
+    pub fn identity(size: i32) -> RotationSummary {
+        RotationSummary {
+            size,
+            offset: 0,
+            zero_landings: vec![0; size as usize],
+            zero_clicks: vec![0; size as usize],
+        }
+    }
+
+    pub fn from_rotation(size: i32, rotation: &DialRotation) -> RotationSummary {
+        let movements = (0..size).map(|start| DialMovement::new(size, start, *rotation)).collect::<Vec<_>>();
+
+        RotationSummary {
+            size,
+            offset: (rotation.get_signed_value().rem_euclid(size as i64)) as i32,
+            zero_landings: movements.iter().map(|m| CountingRule::LandingsOnZero.count_movement(m)).collect(),
+            zero_clicks: movements.iter().map(|m| CountingRule::ClicksThroughZero.count_movement(m)).collect(),
+        }
+    }
+
+    /// Summarises a whole run in O(rotations + size). Starting `s` clicks further round is the
+    /// same as starting at 0 and looking for position `-s` instead of 0, so one run from 0 and
+    /// its visit histogram give the counts for every start at once.
+    pub fn from_rotations(size: i32, rotations: &[DialRotation]) -> Result<RotationSummary, String> {
+        let dial = Dial::with_size(size, 0, 0)?;
+        let clicks_from_zero = visit_histogram(&dial, rotations);
+
+        let mut landings_from_zero = vec![0u64; size as usize];
+        let mut position = 0;
+        for rotation in rotations {
+            position = DialMovement::new(size, position, *rotation).end;
+            landings_from_zero[position as usize] += 1;
+        }
+
+        let looked_up = |counts: &[u64]| (0..size).map(|start| counts[((size - start) % size) as usize]).collect();
+
+        Ok(RotationSummary {
+            size,
+            offset: position,
+            zero_landings: looked_up(&landings_from_zero),
+            zero_clicks: looked_up(clicks_from_zero.counts()),
+        })
+    }
+
+    pub fn get_size(&self) -> i32 {
+        self.size
+    }
+
+    /// How far the run moves the dial to the right, modulo its size.
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+
+    pub fn end_position(&self, start: i32) -> i32 {
+        ((start as i64 + self.offset as i64) % self.size as i64) as i32
+    }
+
+    pub fn count(&self, rule: CountingRule, start: i32) -> u64 {
+        match rule {
+            CountingRule::LandingsOnZero => self.zero_landings[start as usize],
+            CountingRule::ClicksThroughZero => self.zero_clicks[start as usize],
+        }
+    }
+
+    /// This run followed by `next`.
+    pub fn then(&self, next: &RotationSummary) -> RotationSummary {
+        assert_eq!(self.size, next.size, "cannot compose summaries of different dial sizes");
+
+        let combine = |mine: &[u64], theirs: &[u64]| {
+            (0..self.size)
+                .map(|start| mine[start as usize] + theirs[self.end_position(start) as usize])
+                .collect()
+        };
+
+        RotationSummary {
+            size: self.size,
+            offset: ((self.offset as i64 + next.offset as i64) % self.size as i64) as i32,
+            zero_landings: combine(&self.zero_landings, &next.zero_landings),
+            zero_clicks: combine(&self.zero_clicks, &next.zero_clicks),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeOutcome {
+    pub end_position: i32,
+    pub zero_landings: u64,
+    pub zero_clicks: u64,
+}
+
+impl RangeOutcome {
+    pub fn starting_at(position: i32) -> RangeOutcome {
+        RangeOutcome { end_position: position, zero_landings: 0, zero_clicks: 0 }
+    }
+
+    /// Carries on from this outcome through the run that `summary` describes.
+    pub fn then(self, summary: &RotationSummary) -> RangeOutcome {
+        RangeOutcome {
+            end_position: summary.end_position(self.end_position),
+            zero_landings: self.zero_landings + summary.count(CountingRule::LandingsOnZero, self.end_position),
+            zero_clicks: self.zero_clicks + summary.count(CountingRule::ClicksThroughZero, self.end_position),
+        }
+    }
+
+    pub fn count(&self, rule: CountingRule) -> u64 {
+        match rule {
+            CountingRule::LandingsOnZero => self.zero_landings,
+            CountingRule::ClicksThroughZero => self.zero_clicks,
+        }
+    }
+}
+
+/// A segment tree of `RotationSummary`s over a rotation list.
+///
+/// Queries walk the O(log n) nodes covering a range in order, looking up one start position in
+/// each, so they never build a combined summary. Replacing a rotation recomposes the O(log n)
+/// nodes above it, each in O(dial size).
+pub struct RotationSegmentTree {
+    size: i32,
+    len: usize,
+    leaf_count: usize,
+    nodes: Vec<RotationSummary>,
+}
+
+impl RotationSegmentTree {
+    pub fn new(size: i32, rotations: &[DialRotation]) -> Result<RotationSegmentTree, String> {
+        if size < 1 {
+            return Err("Invalid dial size".to_string());
+        }
+
+        let leaf_count = rotations.len().next_power_of_two();
+        let mut nodes = vec![RotationSummary::identity(size); 2 * leaf_count];
+        for (index, rotation) in rotations.iter().enumerate() {
+            nodes[leaf_count + index] = RotationSummary::from_rotation(size, rotation);
+        }
+        for node in (1..leaf_count).rev() {
+            nodes[node] = nodes[2 * node].then(&nodes[2 * node + 1]);
+        }
+
+        Ok(RotationSegmentTree { size, len: rotations.len(), leaf_count, nodes })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn set(&mut self, index: usize, rotation: &DialRotation) {
+        assert!(index < self.len, "rotation index {} out of range for {} rotations", index, self.len);
+
+        let mut node = self.leaf_count + index;
+        self.nodes[node] = RotationSummary::from_rotation(self.size, rotation);
+        while node > 1 {
+            node /= 2;
+            self.nodes[node] = self.nodes[2 * node].then(&self.nodes[2 * node + 1]);
+        }
+    }
+
+    /// Runs the rotations in `range` from `start` and reports where the dial ends and what it counted.
+    pub fn query(&self, range: Range<usize>, start: i32) -> RangeOutcome {
+        assert!(range.start <= range.end && range.end <= self.len, "rotation range {:?} out of range for {} rotations", range, self.len);
+        assert!((0..self.size).contains(&start), "invalid start position {}", start);
+
+        let mut left_nodes = Vec::new();
+        let mut right_nodes = Vec::new();
+        let (mut left, mut right) = (range.start + self.leaf_count, range.end + self.leaf_count);
+        while left < right {
+            if left % 2 == 1 {
+                left_nodes.push(left);
+                left += 1;
+            }
+            if right % 2 == 1 {
+                right -= 1;
+                right_nodes.push(right);
+            }
+            left /= 2;
+            right /= 2;
+        }
+
+        left_nodes
+            .into_iter()
+            .chain(right_nodes.into_iter().rev())
+            .fold(RangeOutcome::starting_at(start), |outcome, node| outcome.then(&self.nodes[node]))
+    }
+
+    pub fn zero_count(&self, rule: CountingRule, range: Range<usize>, start: i32) -> u64 {
+        self.query(range, start).count(rule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_dial_rotation, test_rotations};
+
+    fn simulate(rotations: &[DialRotation], start: i32) -> RangeOutcome {
+        let mut dial = Dial::new(start, 0).unwrap();
+        let mut outcome = RangeOutcome::starting_at(start);
+        for rotation in rotations {
+            let movement = dial.rotate(rotation);
+            outcome.zero_landings += CountingRule::LandingsOnZero.count_movement(&movement);
+            outcome.zero_clicks += CountingRule::ClicksThroughZero.count_movement(&movement);
+        }
+        outcome.end_position = dial.get_current_position();
+        outcome
+    }
+
+    #[test]
+    fn summary_of_test_input_matches_both_parts() {
+        let summary = RotationSummary::from_rotations(100, &test_rotations()).unwrap();
+
+        assert_eq!(summary.count(CountingRule::LandingsOnZero, 50), 3);
+        assert_eq!(summary.count(CountingRule::ClicksThroughZero, 50), 6);
+        assert_eq!(summary.end_position(50), 32);
+    }
+
+    #[test]
+    fn summary_of_run_matches_composing_single_rotations() {
+        let rotations = test_rotations();
+
+        let composed = rotations
+            .iter()
+            .fold(RotationSummary::identity(100), |summary, rotation| summary.then(&RotationSummary::from_rotation(100, rotation)));
+
+        assert_eq!(RotationSummary::from_rotations(100, &rotations).unwrap(), composed);
+    }
+
+    #[test]
+    fn composing_summaries_is_associative() {
+        let rotations = test_rotations();
+        let a = RotationSummary::from_rotations(100, &rotations[..3]).unwrap();
+        let b = RotationSummary::from_rotations(100, &rotations[3..7]).unwrap();
+        let c = RotationSummary::from_rotations(100, &rotations[7..]).unwrap();
+
+        assert_eq!(a.then(&b).then(&c), a.then(&b.then(&c)));
+        assert_eq!(a.then(&RotationSummary::identity(100)), a);
+    }
+
+    #[test]
+    fn summary_of_invalid_dial_size_is_an_error() {
+        assert!(RotationSummary::from_rotations(0, &test_rotations()).is_err());
+        assert!(RotationSummary::from_rotations(-1, &[]).is_err());
+    }
+
+    #[test]
+    fn every_range_query_matches_simulation() {
+        let rotations = test_rotations();
+        let tree = RotationSegmentTree::new(100, &rotations).unwrap();
+
+        for i in 0..=rotations.len() {
+            for j in i..=rotations.len() {
+                for start in [0, 1, 32, 50, 99] {
+                    assert_eq!(tree.query(i..j, start), simulate(&rotations[i..j], start), "{}..{} from {}", i, j, start);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn point_update_changes_later_queries() {
+        let mut rotations = test_rotations();
+        let mut tree = RotationSegmentTree::new(100, &rotations).unwrap();
+
+        rotations[2] = get_dial_rotation("R248");
+        tree.set(2, &rotations[2]);
+
+        assert_eq!(tree.query(0..rotations.len(), 50), simulate(&rotations, 50));
+        assert_eq!(tree.zero_count(CountingRule::ClicksThroughZero, 1..4, 82), simulate(&rotations[1..4], 82).zero_clicks);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{Dial, DialRotation};
+use std::borrow::Borrow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetCount {
+    pub target: i32,
+    /// Rotations that finished on the target (the part 1 rule).
+    pub landings: u64,
+    /// Clicks that landed on the target (the part 2 rule).
+    pub clicks: u64,
+}
+
+/// Counts landings on and clicks through every target in a single pass over the rotations.
+///
+/// Each rotation adds its full turns to every target at once and its remaining partial arc to
+/// the contiguous run of sorted targets it covers, so the cost is one binary search per rotation
+/// rather than one simulation per target. Results come back sorted by target, without duplicates.
+pub fn count_target_hits<R>(dial: &Dial, rotations: R, targets: &[i32]) -> Result<Vec<TargetCount>, String>
+where
+    R: IntoIterator,
+    R::Item: Borrow<DialRotation>,
+{
+    let size = dial.get_size();
+    if let Some(target) = targets.iter().find(|t| !(0..size).contains(*t)) {
+        return Err(format!("Invalid target position {} for a dial of size {}", target, size));
+    }
+
+    let mut sorted_targets = targets.to_vec();
+    sorted_targets.sort_unstable();
+    sorted_targets.dedup();
+
+    let mut landings = vec![0u64; sorted_targets.len()];
+    let mut partial_arc_clicks = vec![0i64; sorted_targets.len() + 1];
+    let mut full_turns = 0u64;
+    let mut dial = dial.clone();
+
+    for rotation in rotations {
+        let movement = dial.rotate(rotation.borrow());
+        full_turns += movement.full_turns();
+
+        if let Some((first_position, clicks)) = movement.partial_arc() {
+            let last_position = first_position as i64 + clicks as i64 - 1;
+
+            if last_position < size as i64 {
+                add_to_range(&mut partial_arc_clicks, &sorted_targets, first_position, last_position as i32);
+            } else {
+                add_to_range(&mut partial_arc_clicks, &sorted_targets, first_position, size - 1);
+                add_to_range(&mut partial_arc_clicks, &sorted_targets, 0, (last_position - size as i64) as i32);
+            }
+        }
+
+        if let Ok(index) = sorted_targets.binary_search(&movement.end) {
+            landings[index] += 1;
+        }
+    }
+
+    let mut running_partial_clicks = 0i64;
+    Ok(sorted_targets
+        .iter()
+        .enumerate()
+        .map(|(index, &target)| {
+            running_partial_clicks += partial_arc_clicks[index];
+            TargetCount {
+                target,
+                landings: landings[index],
+                clicks: full_turns + running_partial_clicks as u64,
+            }
+        })
+        .collect())
+}
+
+fn add_to_range(differences: &mut [i64], sorted_targets: &[i32], first_position: i32, last_position: i32) {
+    let start = sorted_targets.partition_point(|&t| t < first_position);
+    let end = sorted_targets.partition_point(|&t| t <= last_position);
+    differences[start] += 1;
+    differences[end] -= 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{count_target_clicks, get_dial_rotation, test_rotations, Direction};
+
+    #[test]
+    fn target_zero_matches_both_parts_for_test_input() {
+        let counts = count_target_hits(&Dial::new(50, 0).unwrap(), test_rotations(), &[0]).unwrap();
+
+        assert_eq!(counts, vec![TargetCount { target: 0, landings: 3, clicks: 6 }]);
+    }
+
+    #[test]
+    fn every_target_matches_per_target_arithmetic() {
+        let rotations = ["R250", "L37", "L199", "R3", "L100", "R0", "L71"].map(get_dial_rotation);
+        let dial = Dial::new(10, 0).unwrap();
+        let all_targets = (0..100).rev().collect::<Vec<_>>();
+
+        let counts = count_target_hits(&dial, rotations, &all_targets).unwrap();
+
+        assert_eq!(counts.len(), 100);
+        for count in counts {
+            let mut position = 10;
+            let mut expected_clicks = 0;
+            let mut expected_landings = 0;
+            for rotation in &rotations {
+                expected_clicks += count_target_clicks(position as i64, rotation.get_signed_value(), count.target as i64, 100);
+                position = (position as i64 + rotation.get_signed_value()).rem_euclid(100) as i32;
+                expected_landings += (position == count.target) as u64;
+            }
+            assert_eq!((count.clicks, count.landings), (expected_clicks, expected_landings), "target {}", count.target);
+        }
+    }
+
+    #[test]
+    fn arcs_past_i32_max_on_large_dials_wrap_to_the_bottom() {
+        let dial = Dial::with_size(2_000_000_000, 1_999_999_999, 0).unwrap();
+        let rotations = [DialRotation::new(Direction::Right, 1_999_999_999)];
+
+        let counts = count_target_hits(&dial, rotations, &[0, 1_999_999_998, 1_999_999_999]).unwrap();
+
+        let clicks = counts.iter().map(|count| (count.target, count.clicks, count.landings)).collect::<Vec<_>>();
+        assert_eq!(clicks, [(0, 1, 0), (1_999_999_998, 1, 1), (1_999_999_999, 0, 0)]);
+    }
+
+    #[test]
+    fn duplicate_targets_are_reported_once() {
+        let counts = count_target_hits(&Dial::new(50, 0).unwrap(), test_rotations(), &[82, 0, 82]).unwrap();
+
+        assert_eq!(counts.iter().map(|c| c.target).collect::<Vec<_>>(), vec![0, 82]);
+    }
+
+    #[test]
+    fn target_outside_the_dial_is_an_error() {
+        let dial = Dial::with_size(12, 0, 0).unwrap();
+
+        assert!(count_target_hits(&dial, test_rotations(), &[3, 12]).is_err());
+    }
+}
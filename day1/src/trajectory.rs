@@ -0,0 +1,132 @@
+use crate::{Dial, DialRotation};
+use std::borrow::Borrow;
+
+/// One rotation as seen by a `Trajectory`. Both the part 1 rule (landing on 0) and the part 2
+/// rule (every click through 0) are tracked, with totals running from the start of the trajectory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DialStep {
+    pub index: usize,
+    pub rotation: DialRotation,
+    pub position_before: i32,
+    pub position_after: i32,
+    pub zero_clicks: u64,
+    pub landed_on_zero: bool,
+    pub total_zero_landings: u64,
+    pub total_zero_clicks: u64,
+}
+
+pub struct Trajectory<I> {
+    dial: Dial,
+    rotations: I,
+    index: usize,
+    total_zero_landings: u64,
+    total_zero_clicks: u64,
+}
+
+impl<I> Trajectory<I>
+where
+    I: Iterator,
+    I::Item: Borrow<DialRotation>,
+{
+    pub fn new<R: IntoIterator<IntoIter = I>>(dial: Dial, rotations: R) -> Trajectory<I> {
+        Trajectory {
+            dial,
+            rotations: rotations.into_iter(),
+            index: 0,
+            total_zero_landings: 0,
+            total_zero_clicks: 0,
+        }
+    }
+
+    pub fn dial(&self) -> &Dial {
+        &self.dial
+    }
+
+    pub fn into_dial(self) -> Dial {
+        self.dial
+    }
+}
+
+impl<I> Iterator for Trajectory<I>
+where
+    I: Iterator,
+    I::Item: Borrow<DialRotation>,
+{
+    type Item = DialStep;
+
+    fn next(&mut self) -> Option<DialStep> {
+        let movement = self.dial.rotate(self.rotations.next()?.borrow());
+        let zero_clicks = movement.zero_clicks();
+        let landed_on_zero = movement.landed_on_zero();
+        self.total_zero_landings += landed_on_zero as u64;
+        self.total_zero_clicks += zero_clicks;
+
+        let step = DialStep {
+            index: self.index,
+            rotation: movement.rotation,
+            position_before: movement.start,
+            position_after: movement.end,
+            zero_clicks,
+            landed_on_zero,
+            total_zero_landings: self.total_zero_landings,
+            total_zero_clicks: self.total_zero_clicks,
+        };
+        self.index += 1;
+        Some(step)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rotations.size_hint()
+    }
+}
+
+pub fn trajectory<R>(dial: Dial, rotations: R) -> Trajectory<R::IntoIter>
+where
+    R: IntoIterator,
+    R::Item: Borrow<DialRotation>,
+{
+    Trajectory::new(dial, rotations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rotations;
+
+    #[test]
+    fn trajectory_yields_one_step_per_rotation() {
+        let steps = trajectory(Dial::new(50, 0).unwrap(), &test_rotations()).collect::<Vec<_>>();
+
+        assert_eq!(steps.len(), 10);
+        assert_eq!(steps.iter().map(|s| s.index).collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn trajectory_steps_chain_positions() {
+        let steps = trajectory(Dial::new(50, 0).unwrap(), test_rotations()).collect::<Vec<_>>();
+
+        assert_eq!(steps[0].position_before, 50);
+        assert_eq!(steps[0].position_after, 82);
+        assert_eq!(steps[0].zero_clicks, 1);
+        assert!(!steps[0].landed_on_zero);
+        for pair in steps.windows(2) {
+            assert_eq!(pair[0].position_after, pair[1].position_before);
+        }
+    }
+
+    #[test]
+    fn trajectory_totals_match_both_parts_for_test_input() {
+        let last = trajectory(Dial::new(50, 0).unwrap(), test_rotations()).last().unwrap();
+
+        assert_eq!(last.total_zero_landings, 3);
+        assert_eq!(last.total_zero_clicks, 6);
+    }
+
+    #[test]
+    fn trajectory_leaves_dial_at_final_position() {
+        let mut steps = trajectory(Dial::new(50, 0).unwrap(), test_rotations());
+        steps.by_ref().for_each(drop);
+
+        assert_eq!(steps.into_dial().get_current_position(), 32);
+    }
+}
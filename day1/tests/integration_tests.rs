@@ -1,6 +1,7 @@
 use day1::*;
 use std::fs::File;
 use std::fs::read_to_string;
+use std::io::BufReader;
 
 #[test]
 fn test_input_file_can_be_opened() {
@@ -22,19 +23,10 @@ fn test_input_contents_are_correct() {
 fn processing_test_input_results_in_3() {
     let mut dial = Dial::new(50, 0).unwrap();
 
-    let test_input = read_to_string("input/test_input.txt").unwrap();
-
-    let dial_rotation_strings = test_input.split('\n').collect::<Vec<&str>>();
-
-    let mut dial_rotations:Vec<DialRotation> = vec![];
-
-    for s in dial_rotation_strings {
-        let dial_rotation = get_dial_rotation(s);
-        dial_rotations.push(dial_rotation);
-    }
+    let dial_rotations = load_dial_rotations("input/test_input.txt").unwrap();
 
     for r in dial_rotations {
-        dial.turn_dial_in_direction_part1(&r);
+        dial.turn_dial_in_direction_part1(&r).unwrap();
     }
 
     assert_eq!(3, dial.get_zero_count());
@@ -44,19 +36,10 @@ fn processing_test_input_results_in_3() {
 fn processing_puzzle_input_results_in_1118() {
     let mut dial = Dial::new(50, 0).unwrap();
 
-    let test_input = read_to_string("input/input.txt").unwrap();
-
-    let dial_rotation_strings = test_input.split('\n').collect::<Vec<&str>>();
-
-    let mut dial_rotations:Vec<DialRotation> = vec![];
-
-    for s in dial_rotation_strings {
-        let dial_rotation = get_dial_rotation(s);
-        dial_rotations.push(dial_rotation);
-    }
+    let dial_rotations = load_dial_rotations("input/input.txt").unwrap();
 
     for r in dial_rotations {
-        dial.turn_dial_in_direction_part1(&r);
+        dial.turn_dial_in_direction_part1(&r).unwrap();
     }
 
     assert_eq!(1118, dial.get_zero_count());
@@ -67,20 +50,135 @@ fn processing_puzzle_input_results_in_1118() {
 fn processing_test_input_results_in_6() {
     let mut dial = Dial::new(50, 0).unwrap();
 
-    let test_input = read_to_string("input/test_input.txt").unwrap();
+    let dial_rotations = load_dial_rotations("input/test_input.txt").unwrap();
+
+    for r in dial_rotations {
+        dial.turn_dial_in_direction_part2(&r).unwrap();
+    }
+
+    assert_eq!(6, dial.get_zero_count());
+}
+
+// ---- Trajectory ----
+#[test]
+fn puzzle_input_trajectory_totals_match_both_parts() {
+    let dial_rotations = load_dial_rotations("input/input.txt").unwrap();
+
+    let last_step = trajectory(Dial::new(50, 0).unwrap(), &dial_rotations).last().unwrap();
+
+    assert_eq!(last_step.index, 4423);
+    assert_eq!(last_step.total_zero_landings, 1118);
+    assert_eq!(last_step.total_zero_clicks, 6289);
+}
+
+// ---- Inverse ----
+#[test]
+fn puzzle_input_answers_are_consistent_with_starting_at_50() {
+    let dial_rotations = load_dial_rotations("input/input.txt").unwrap();
+
+    let part1 = infer_start_positions(100, &dial_rotations, LandingsOnZero::default, 1118).unwrap();
+    let part2 = infer_start_positions(100, &dial_rotations, ClicksThroughZero::default, 6289).unwrap();
+
+    assert!(part1.matching_starts.contains(&50));
+    assert!(part2.matching_starts.contains(&50));
+}
 
-    let dial_rotation_strings = test_input.split('\n').collect::<Vec<&str>>();
+// ---- Summaries ----
+#[test]
+fn puzzle_input_segment_tree_matches_both_parts() {
+    let dial_rotations = load_dial_rotations("input/input.txt").unwrap();
 
-    let mut dial_rotations:Vec<DialRotation> = vec![];
+    let tree = RotationSegmentTree::new(100, &dial_rotations).unwrap();
 
-    for s in dial_rotation_strings {
-        let dial_rotation = get_dial_rotation(s);
-        dial_rotations.push(dial_rotation);
+    assert_eq!(tree.zero_count(CountingRule::LandingsOnZero, 0..tree.len(), 50), 1118);
+    assert_eq!(tree.zero_count(CountingRule::ClicksThroughZero, 0..tree.len(), 50), 6289);
+}
+
+// ---- Snapshots ----
+#[test]
+fn puzzle_input_resumed_from_snapshot_matches_both_parts() {
+    let dial_rotations = load_dial_rotations("input/input.txt").unwrap();
+
+    for (rule, expected) in [(CountingRule::LandingsOnZero, 1118), (CountingRule::ClicksThroughZero, 6289)] {
+        let mut runner = RotationRunner::new(Dial::new(50, 0).unwrap(), rule);
+        runner.run_for(&dial_rotations, 2000).unwrap();
+        let saved = runner.snapshot().to_bytes();
+
+        let mut resumed = RotationRunner::resume(&DialSnapshot::from_bytes(&saved).unwrap(), &dial_rotations).unwrap();
+        resumed.run(&dial_rotations).unwrap();
+
+        assert_eq!(resumed.dial().get_zero_count(), expected);
+    }
+}
+
+// ---- Export ----
+#[test]
+fn puzzle_input_svg_reports_both_parts() {
+    let dial_rotations = load_dial_rotations("input/input.txt").unwrap();
+    let samples = TrajectorySamples::from_steps(100, trajectory(Dial::new(50, 0).unwrap(), &dial_rotations), 1000);
+    let mut svg = Vec::new();
+
+    write_trajectory_svg(&samples, &mut svg).unwrap();
+
+    assert!(samples.buckets().len() <= 1000);
+    assert!(String::from_utf8(svg).unwrap().contains("4424 steps, 1118 landings on 0, 6289 clicks through 0"));
+}
+
+// ---- Generator ----
+#[test]
+fn generated_input_answers_match_both_parts() {
+    let path = std::env::temp_dir().join(format!("day1_generated_{}.txt", std::process::id()));
+    let config = GeneratorConfig { seed: 2025, edge_cases: EdgeCaseDensity { zero_landings: 0.1, whole_turns: 0.1, zero_starts: 0.1 }, ..GeneratorConfig::default() };
+
+    let written = write_generated_input(&config, &path).unwrap();
+    let dial_rotations = load_dial_rotations(&path).unwrap();
+    let answers = read_to_string(answers_path(&path)).unwrap().parse::<ExpectedAnswers>().unwrap();
+    std::fs::remove_file(answers_path(&path)).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut part1 = Dial::new(50, 0).unwrap();
+    let mut part2 = Dial::new(50, 0).unwrap();
+    for r in &dial_rotations {
+        part1.turn_dial_in_direction_part1(r).unwrap();
+        part2.turn_dial_in_direction_part2(r).unwrap();
     }
 
+    assert_eq!(answers, written);
+    assert_eq!(dial_rotations.len(), 4424);
+    assert_eq!(part1.get_zero_count() as u64, answers.part1);
+    assert_eq!(part2.get_zero_count() as u64, answers.part2);
+}
+
+// ---- Parsing ----
+#[test]
+fn parsing_puzzle_input_leniently_has_no_diagnostics() {
+    let file = File::open("input/input.txt").unwrap();
+
+    let parsed = parse_dial_rotations(BufReader::new(file), ParseMode::Lenient).unwrap();
+
+    assert!(parsed.is_clean());
+    assert_eq!(parsed.rotations.len(), 4424);
+}
+
+#[test]
+fn loading_missing_file_reports_the_path() {
+    let error = load_dial_rotations("input/does_not_exist.txt").unwrap_err();
+
+    assert!(error.starts_with("input/does_not_exist.txt"));
+}
+
+// ---- Widths ----
+#[test]
+fn processing_puzzle_input_as_u64_matches_both_parts() {
+    let mut part1 = Dial::<u64>::new(50, 0).unwrap();
+    let mut part2 = Dial::<u64>::new(50, 0).unwrap();
+
+    let dial_rotations = load_dial_rotations_as::<u64, _>("input/input.txt").unwrap();
+
     for r in dial_rotations {
-        dial.turn_dial_in_direction_part2(&r);
+        part1.turn_dial_in_direction_part1(&r).unwrap();
+        part2.turn_dial_in_direction_part2(&r).unwrap();
     }
 
-    assert_eq!(6, dial.get_zero_count());
+    assert_eq!((part1.get_zero_count(), part2.get_zero_count()), (1118, 6289));
 }